@@ -4,7 +4,7 @@ use claude_status::config::{Config, LineWidgetConfig, PowerlineConfig};
 use claude_status::layout::LayoutEngine;
 use claude_status::render::Renderer;
 use claude_status::widgets::data::*;
-use claude_status::widgets::{SessionData, WidgetRegistry};
+use claude_status::widgets::{RenderContext, SessionData, WidgetRegistry};
 use std::collections::HashMap;
 
 fn mock_session() -> SessionData {
@@ -47,6 +47,7 @@ fn mock_session() -> SessionData {
         exceeds_200k_tokens: Some(false),
         vim: None,
         agent: None,
+        raw: None,
     }
 }
 
@@ -61,6 +62,8 @@ fn widget(widget_type: &str) -> LineWidgetConfig {
         padding: None,
         merge_next: false,
         metadata: HashMap::new(),
+        thresholds: HashMap::new(),
+        when: None,
     }
 }
 
@@ -75,12 +78,12 @@ fn bench_default_render(c: &mut Criterion) {
     let data = mock_session();
     let config = Config::default();
     let renderer = Renderer::detect("none");
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
 
     c.bench_function("default_render", |b| {
         b.iter(|| {
             let engine = LayoutEngine::new(&config, &renderer);
-            engine.render(&data, &config, &registry)
+            engine.render(&data, &registry)
         })
     });
 }
@@ -105,12 +108,12 @@ fn bench_powerline_render(c: &mut Criterion) {
         ..Config::default()
     };
     let renderer = Renderer::detect("truecolor");
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
 
     c.bench_function("powerline_render", |b| {
         b.iter(|| {
             let engine = LayoutEngine::new(&config, &renderer);
-            engine.render(&data, &config, &registry)
+            engine.render(&data, &registry)
         })
     });
 }
@@ -149,7 +152,7 @@ fn bench_json_parsing(c: &mut Criterion) {
 
 fn bench_single_widget(c: &mut Criterion) {
     let data = mock_session();
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let config = claude_status::widgets::WidgetConfig {
         widget_type: "context-percentage".into(),
         id: "bench".into(),
@@ -160,10 +163,11 @@ fn bench_single_widget(c: &mut Criterion) {
         padding: None,
         merge_next: false,
         metadata: HashMap::new(),
+        compact: false,
     };
 
     c.bench_function("single_widget_render", |b| {
-        b.iter(|| registry.render("context-percentage", &data, &config))
+        b.iter(|| registry.render("context-percentage", &data, &RenderContext::new(), &config))
     });
 }
 
@@ -184,12 +188,12 @@ fn bench_multiline_full(c: &mut Criterion) {
         ..Config::default()
     };
     let renderer = Renderer::detect("truecolor");
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
 
     c.bench_function("multiline_full_render", |b| {
         b.iter(|| {
             let engine = LayoutEngine::new(&config, &renderer);
-            engine.render(&data, &config, &registry)
+            engine.render(&data, &registry)
         })
     });
 }
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::Period;
+
+/// Spending limits in USD for the projects matching one `[budgets]` glob.
+/// Each project matching the glob gets the full limits to itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    pub daily: Option<f64>,
+    pub weekly: Option<f64>,
+    pub monthly: Option<f64>,
+}
+
+impl Budget {
+    /// The limits that are set, shortest period first.
+    pub fn limits(&self) -> impl Iterator<Item = (Period, f64)> {
+        [
+            (Period::Day, self.daily),
+            (Period::Week, self.weekly),
+            (Period::Month, self.monthly),
+        ]
+        .into_iter()
+        .filter_map(|(period, limit)| Some((period, limit?)))
+    }
+}
+
+/// The `[budgets]` table from the config, keyed by project directory glob
+/// with `~` expanded. Cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct Budgets {
+    globs: Arc<HashMap<String, Budget>>,
+}
+
+impl Budgets {
+    pub fn new(config: &HashMap<String, Budget>) -> Self {
+        let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string());
+        Self {
+            globs: Arc::new(
+                config
+                    .iter()
+                    .map(|(glob, budget)| {
+                        let glob = match (glob.strip_prefix('~'), &home) {
+                            (Some(rest), Some(home)) => format!("{home}{rest}"),
+                            _ => glob.clone(),
+                        };
+                        (glob, *budget)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The budget for a project directory. Like pricing fragments, the
+    /// longest matching glob wins, so `~/work/acme` takes precedence over
+    /// `~/work/*`.
+    pub fn for_project(&self, dir: &str) -> Option<&Budget> {
+        self.globs
+            .iter()
+            .filter(|(glob, _)| glob_match(glob, dir))
+            .max_by_key(|(glob, _)| glob.len())
+            .map(|(_, budget)| budget)
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of
+/// characters, `/` included, and `?` any single character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and the text position it has swallowed up to
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, swallowed)) => {
+                    p = star + 1;
+                    t = swallowed + 1;
+                    backtrack = Some((star, t));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/work/*", "/work/acme"));
+        assert!(glob_match("/work/*", "/work/acme/api"));
+        assert!(glob_match("*", "/anything"));
+        assert!(glob_match("/work/acme-?", "/work/acme-2"));
+        assert!(glob_match("/work/*/api", "/work/acme/api"));
+        assert!(glob_match("/work/acme", "/work/acme"));
+        assert!(!glob_match("/work/acme", "/work/acme-2"));
+        assert!(!glob_match("/work/*/api", "/work/acme/web"));
+        assert!(!glob_match("/home/*", "/work/acme"));
+    }
+
+    #[test]
+    fn test_longest_matching_glob_wins() {
+        let daily = |limit| Budget {
+            daily: Some(limit),
+            ..Default::default()
+        };
+        let budgets = Budgets::new(&HashMap::from([
+            ("*".to_string(), daily(50.0)),
+            ("/work/*".to_string(), daily(20.0)),
+            ("/work/acme".to_string(), daily(5.0)),
+        ]));
+        assert_eq!(budgets.for_project("/work/acme"), Some(&daily(5.0)));
+        assert_eq!(budgets.for_project("/work/globex"), Some(&daily(20.0)));
+        assert_eq!(budgets.for_project("/home/me/notes"), Some(&daily(50.0)));
+        assert_eq!(Budgets::default().for_project("/work/acme"), None);
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt;
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, Months, NaiveDate, TimeZone, Utc, Weekday,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// A span of time that spending limits cover, up to now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+    /// A billing cycle, from one `billing_day` to the next.
+    Month,
+}
+
+impl Period {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Day => "today",
+            Self::Week => "this week",
+            Self::Month => "this month",
+        }
+    }
+}
+
+/// When days, weeks and months begin: the `[calendar]` config section.
+/// Stats, cost limits and budgets all reset on these boundaries.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Calendar {
+    #[serde(default)]
+    pub timezone: Timezone,
+    /// First day of the week, e.g. `monday` or `sun`.
+    #[serde(default = "default_week_start")]
+    pub week_start: Weekday,
+    /// Day of the month monthly limits reset on. Months too short for it
+    /// reset on their last day.
+    #[serde(
+        default = "default_billing_day",
+        deserialize_with = "deserialize_billing_day"
+    )]
+    pub billing_day: u32,
+}
+
+fn default_week_start() -> Weekday {
+    Weekday::Mon
+}
+fn default_billing_day() -> u32 {
+    1
+}
+
+fn deserialize_billing_day<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let day = u32::deserialize(deserializer)?;
+    if (1..=31).contains(&day) {
+        Ok(day)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "billing_day must be between 1 and 31, got {day}"
+        )))
+    }
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            timezone: Timezone::default(),
+            week_start: default_week_start(),
+            billing_day: default_billing_day(),
+        }
+    }
+}
+
+impl Calendar {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Unix timestamp the period containing `now` started at.
+    pub fn start(&self, period: Period, now: DateTime<Utc>) -> i64 {
+        self.timezone.midnight(self.first_day(period, now))
+    }
+
+    /// Unix timestamp the period containing `now` ends, and the next starts, at.
+    pub fn end(&self, period: Period, now: DateTime<Utc>) -> i64 {
+        let first = self.first_day(period, now);
+        let next = match period {
+            Period::Day => first + Days::new(1),
+            Period::Week => first + Days::new(7),
+            Period::Month => {
+                let next_month = first.with_day(1).unwrap() + Months::new(1);
+                self.billing_date(next_month.year(), next_month.month())
+            }
+        };
+        self.timezone.midnight(next)
+    }
+
+    fn first_day(&self, period: Period, now: DateTime<Utc>) -> NaiveDate {
+        let today = self.timezone.date(now);
+        match period {
+            Period::Day => today,
+            Period::Week => today - Days::new(today.weekday().days_since(self.week_start) as u64),
+            Period::Month => {
+                let this_month = self.billing_date(today.year(), today.month());
+                if today >= this_month {
+                    this_month
+                } else {
+                    let last_month = today.with_day(1).unwrap() - Months::new(1);
+                    self.billing_date(last_month.year(), last_month.month())
+                }
+            }
+        }
+    }
+
+    /// The month's `billing_day`, or its last day if it has fewer days.
+    fn billing_date(&self, year: i32, month: u32) -> NaiveDate {
+        let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        let last = (first + Months::new(1) - Days::new(1)).day();
+        first.with_day(self.billing_day.min(last)).unwrap()
+    }
+}
+
+/// The time zone days are counted in: UTC, the system's local zone
+/// (which follows `TZ` and daylight saving time), or a fixed offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Timezone {
+    #[default]
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    /// The calendar date at `now` in this zone.
+    pub fn date(&self, now: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Self::Utc => now.date_naive(),
+            Self::Local => now.with_timezone(&Local).date_naive(),
+            Self::Fixed(offset) => now.with_timezone(offset).date_naive(),
+        }
+    }
+
+    /// Unix timestamp of 00:00 on `date` in this zone. Where daylight saving
+    /// time skips midnight, the day starts at the first hour that exists.
+    pub fn midnight(&self, date: NaiveDate) -> i64 {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        match self {
+            Self::Utc => midnight.and_utc().timestamp(),
+            Self::Local => (0..=2)
+                .find_map(|h| {
+                    Local
+                        .from_local_datetime(&(midnight + chrono::Duration::hours(h)))
+                        .earliest()
+                })
+                .map_or_else(|| midnight.and_utc().timestamp(), |t| t.timestamp()),
+            Self::Fixed(offset) => (midnight - *offset).and_utc().timestamp(),
+        }
+    }
+
+    /// Format a Unix timestamp as local time in this zone.
+    pub fn format(&self, timestamp: i64, format: &str) -> String {
+        let Some(utc) = DateTime::from_timestamp(timestamp, 0) else {
+            return String::new();
+        };
+        match self {
+            Self::Utc => utc.format(format).to_string(),
+            Self::Local => utc.with_timezone(&Local).format(format).to_string(),
+            Self::Fixed(offset) => utc.with_timezone(offset).format(format).to_string(),
+        }
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = String;
+
+    /// `utc`, `local`, or an offset such as `-08:00`, `+0530` or `+9`.
+    fn try_from(s: String) -> Result<Self, String> {
+        let invalid =
+            || format!("invalid timezone \"{s}\" (use utc, local or an offset like -08:00)");
+        match s.to_lowercase().as_str() {
+            "utc" => return Ok(Self::Utc),
+            "local" => return Ok(Self::Local),
+            _ => {}
+        }
+
+        let (sign, rest) = match s.split_at_checked(1) {
+            Some(("+", rest)) => (1, rest),
+            Some(("-", rest)) => (-1, rest),
+            _ => return Err(invalid()),
+        };
+        let digits: String = rest.chars().filter(|&c| c != ':').collect();
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let (hours, minutes) = match digits.len() {
+            1 | 2 => (digits.as_str(), "0"),
+            4 => digits.split_at(2),
+            _ => return Err(invalid()),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(tz: Timezone) -> Self {
+        tz.to_string()
+    }
+}
+
+impl fmt::Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utc => write!(f, "utc"),
+            Self::Local => write!(f, "local"),
+            Self::Fixed(offset) => write!(f, "{offset}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn calendar(timezone: &str, week_start: Weekday, billing_day: u32) -> Calendar {
+        Calendar {
+            timezone: Timezone::try_from(timezone.to_string()).unwrap(),
+            week_start,
+            billing_day,
+        }
+    }
+
+    #[test]
+    fn test_default_periods_are_utc() {
+        // Wednesday 2026-03-11 15:30 UTC
+        let now = at("2026-03-11T15:30:00Z");
+        let cal = Calendar::default();
+        let ts = |s| at(s).timestamp();
+        assert_eq!(cal.start(Period::Day, now), ts("2026-03-11T00:00:00Z"));
+        assert_eq!(cal.start(Period::Week, now), ts("2026-03-09T00:00:00Z"));
+        assert_eq!(cal.start(Period::Month, now), ts("2026-03-01T00:00:00Z"));
+        assert_eq!(cal.end(Period::Week, now), ts("2026-03-16T00:00:00Z"));
+        assert_eq!(cal.end(Period::Month, now), ts("2026-04-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_offset_shifts_day_boundaries() {
+        // 03:00 UTC on Thursday is still Wednesday evening in UTC-8
+        let now = at("2026-03-12T03:00:00Z");
+        let cal = calendar("-08:00", Weekday::Sun, 1);
+        let ts = |s| at(s).timestamp();
+        assert_eq!(cal.start(Period::Day, now), ts("2026-03-11T00:00:00-08:00"));
+        assert_eq!(
+            cal.start(Period::Week, now),
+            ts("2026-03-08T00:00:00-08:00")
+        );
+        assert_eq!(cal.end(Period::Day, now), ts("2026-03-12T00:00:00-08:00"));
+    }
+
+    #[test]
+    fn test_billing_day_anchors_months() {
+        let cal = calendar("utc", Weekday::Mon, 15);
+        let ts = |s| at(s).timestamp();
+        let now = at("2026-03-11T12:00:00Z");
+        assert_eq!(cal.start(Period::Month, now), ts("2026-02-15T00:00:00Z"));
+        assert_eq!(cal.end(Period::Month, now), ts("2026-03-15T00:00:00Z"));
+        let now = at("2026-03-15T00:00:00Z");
+        assert_eq!(cal.start(Period::Month, now), ts("2026-03-15T00:00:00Z"));
+
+        // February has no 31st
+        let cal = calendar("utc", Weekday::Mon, 31);
+        let now = at("2026-03-11T12:00:00Z");
+        assert_eq!(cal.start(Period::Month, now), ts("2026-02-28T00:00:00Z"));
+        assert_eq!(cal.end(Period::Month, now), ts("2026-03-31T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        let parse = |s: &str| Timezone::try_from(s.to_string());
+        assert_eq!(parse("UTC"), Ok(Timezone::Utc));
+        assert_eq!(parse("local"), Ok(Timezone::Local));
+        let offset = |secs| Ok(Timezone::Fixed(FixedOffset::east_opt(secs).unwrap()));
+        assert_eq!(parse("-08:00"), offset(-8 * 3600));
+        assert_eq!(parse("+0530"), offset(5 * 3600 + 1800));
+        assert_eq!(parse("+9"), offset(9 * 3600));
+        assert!(parse("America/Los_Angeles").is_err());
+        assert!(parse("+25:00").is_err());
+        assert_eq!(parse("-08:00").unwrap().to_string(), "-08:00");
+    }
+}
@@ -1,10 +1,14 @@
 use std::collections::HashMap;
 
-use chrono::Datelike;
 use clap::Subcommand;
 
+use claude_status::CostTracker;
+use claude_status::calendar::{Period, Timezone};
 use claude_status::config::{Config, LineWidgetConfig, PowerlineConfig};
-use claude_status::themes::Theme;
+use claude_status::render::Background;
+use claude_status::storage::{CostGroup, GroupBy, SCHEMA_VERSION};
+use claude_status::themes::{Theme, UserTheme};
+use claude_status::widgets::{PluginWidget, WidgetRegistry};
 
 #[derive(Subcommand)]
 pub enum Commands {
@@ -26,6 +30,11 @@ pub enum Commands {
     },
     /// Dump the expected JSON input schema
     DumpSchema,
+    /// Check the config file for errors
+    Validate {
+        /// Config file to check (defaults to the active config)
+        path: Option<String>,
+    },
     /// Manage Pro license
     License {
         #[command(subcommand)]
@@ -36,6 +45,26 @@ pub enum Commands {
         /// Time period: daily, weekly, monthly
         #[arg(long, default_value = "weekly")]
         period: String,
+        /// Output format: text, json, csv, table
+        #[arg(long, default_value = "text")]
+        format: String,
+        /// Start date (YYYY-MM-DD, inclusive); defaults to the start of the period
+        #[arg(long)]
+        from: Option<String>,
+        /// End date (YYYY-MM-DD, inclusive); defaults to today
+        #[arg(long)]
+        to: Option<String>,
+        /// Break totals down by: day, model, project
+        #[arg(long)]
+        group_by: Option<String>,
+        /// Weekly spending limit in USD
+        #[arg(long, default_value_t = 200.0)]
+        weekly_limit: f64,
+    },
+    /// Maintain the cost history database
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
     },
 }
 
@@ -45,6 +74,8 @@ pub enum ThemeAction {
     List,
     /// Set active theme
     Set { name: String },
+    /// Ask the terminal for its background color, for `theme = "auto"`
+    Detect,
 }
 
 #[derive(Subcommand)]
@@ -60,7 +91,17 @@ pub enum LicenseAction {
     Status,
 }
 
-pub fn handle_command(cmd: Commands) {
+#[derive(Subcommand)]
+pub enum DbAction {
+    /// Check the schema version and integrity
+    Check,
+    /// Compact the database file
+    Vacuum,
+    /// Upgrade the schema to the latest version
+    Migrate,
+}
+
+pub fn handle_command(cmd: Commands, config: Option<&str>) {
     match cmd {
         Commands::Config => {
             if let Err(e) = claude_status::tui::run_tui() {
@@ -72,15 +113,37 @@ pub fn handle_command(cmd: Commands) {
         Commands::Theme { action } => match action {
             ThemeAction::List => cmd_theme_list(),
             ThemeAction::Set { name } => cmd_theme_set(&name),
+            ThemeAction::Detect => cmd_theme_detect(),
         },
         Commands::Preset { name } => cmd_preset(&name),
         Commands::DumpSchema => cmd_dump_schema(),
+        Commands::Validate { path } => cmd_validate(path.as_deref()),
         Commands::License { action } => match action {
             LicenseAction::Activate { key } => cmd_license_activate(&key),
             LicenseAction::Deactivate => cmd_license_deactivate(),
             LicenseAction::Status => cmd_license_status(),
         },
-        Commands::Stats { period } => cmd_stats(&period),
+        Commands::Stats {
+            period,
+            format,
+            from,
+            to,
+            group_by,
+            weekly_limit,
+        } => cmd_stats(StatsArgs {
+            period,
+            format,
+            from,
+            to,
+            group_by,
+            weekly_limit,
+            config: config.map(String::from),
+        }),
+        Commands::Db { action } => match action {
+            DbAction::Check => cmd_db_check(),
+            DbAction::Vacuum => cmd_db_vacuum(),
+            DbAction::Migrate => cmd_db_migrate(),
+        },
     }
 }
 
@@ -217,11 +280,33 @@ fn cmd_theme_list() {
     for name in Theme::list() {
         println!("  {name}");
     }
+    println!("  auto (auto_light or auto_dark, to match the terminal background)");
+
+    let Some(dir) = UserTheme::dir() else {
+        return;
+    };
+    let user = UserTheme::load_dir(&dir);
+    if user.is_empty() {
+        return;
+    }
+    println!();
+    println!("User themes ({}):", dir.display());
+    for theme in user {
+        let inherits = theme
+            .inherits
+            .as_deref()
+            .map(|base| format!(" (inherits {base})"))
+            .unwrap_or_default();
+        println!("  {}{inherits}", theme.theme.name);
+        for e in &theme.errors {
+            println!("    [!!] {e}");
+        }
+    }
 }
 
 fn cmd_theme_set(name: &str) {
-    let available = Theme::list();
-    if !available.contains(&name) {
+    let available = Theme::available();
+    if name != "auto" && !available.iter().any(|t| t == name) {
         eprintln!(
             "Unknown theme '{name}'. Available: {}",
             available.join(", ")
@@ -248,6 +333,176 @@ fn cmd_theme_set(name: &str) {
     }
 }
 
+fn cmd_theme_detect() {
+    match claude_status::Renderer::query_background() {
+        Ok((background, path)) => {
+            let name = match background {
+                Background::Light => "light",
+                Background::Dark => "dark",
+            };
+            println!("Terminal background: {name} (saved to {})", path.display());
+        }
+        Err(e) => {
+            eprintln!("Could not detect the terminal background: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn cmd_validate(path: Option<&str>) {
+    let shown = path
+        .map(std::path::PathBuf::from)
+        .or_else(Config::default_path)
+        .unwrap_or_else(config_path);
+    if path.is_none() && !shown.exists() {
+        println!("No config file at {}; using defaults.", shown.display());
+        return;
+    }
+
+    let mut registry = WidgetRegistry::builtin();
+    if let Some(dir) = PluginWidget::dir() {
+        registry.register_plugins(&dir);
+    }
+    match Config::load_checked(path, &registry, &UserTheme::installed()) {
+        Ok(_) => println!("{} is valid", shown.display()),
+        Err(errors) => {
+            for error in &errors {
+                match error.line {
+                    Some(_) => eprintln!("{}:{error}", shown.display()),
+                    None => eprintln!("{}: {error}", shown.display()),
+                }
+            }
+            eprintln!();
+            eprintln!("{} problem(s) found", errors.len());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The history database, unmigrated, or `None` if there isn't one yet.
+fn open_history_db() -> Option<CostTracker> {
+    let path = CostTracker::db_path();
+    if !path.exists() {
+        println!("No history database at {}", path.display());
+        return None;
+    }
+    match CostTracker::open_unmigrated() {
+        Ok(tracker) => Some(tracker),
+        Err(e) => {
+            eprintln!("Error opening {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+fn db_size() -> u64 {
+    std::fs::metadata(CostTracker::db_path())
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+fn cmd_db_check() {
+    let Some(tracker) = open_history_db() else {
+        return;
+    };
+    let check = match tracker.check() {
+        Ok(check) => check,
+        Err(e) => {
+            eprintln!("Error checking database: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    println!("claude-status db check");
+    println!("=================");
+    println!();
+    println!(
+        "  - Path: {} ({} KiB)",
+        CostTracker::db_path().display(),
+        db_size() / 1024
+    );
+
+    let version_ok = check.version == SCHEMA_VERSION;
+    let version = match check.version.cmp(&SCHEMA_VERSION) {
+        std::cmp::Ordering::Equal => format!("Schema: version {} (latest)", check.version),
+        std::cmp::Ordering::Less => format!(
+            "Schema: version {} of {} (run `claude-status db migrate`)",
+            check.version, SCHEMA_VERSION
+        ),
+        std::cmp::Ordering::Greater => format!(
+            "Schema: version {} is newer than this build supports ({})",
+            check.version, SCHEMA_VERSION
+        ),
+    };
+    print_check(version_ok, &version);
+
+    let integrity_ok = check.integrity_errors.is_empty();
+    print_check(integrity_ok, "Integrity check");
+    for error in &check.integrity_errors {
+        println!("     {error}");
+    }
+
+    println!("  - Sessions: {}", check.sessions);
+    println!("  - Events: {}", check.events);
+    let orphans_ok = check.orphaned_events == 0;
+    print_check(
+        orphans_ok,
+        &format!("Orphaned events: {}", check.orphaned_events),
+    );
+
+    if !(version_ok && integrity_ok && orphans_ok) {
+        std::process::exit(1);
+    }
+}
+
+fn cmd_db_vacuum() {
+    let Some(tracker) = open_history_db() else {
+        return;
+    };
+    let before = db_size();
+    if let Err(e) = tracker.vacuum() {
+        eprintln!("Error vacuuming database: {e}");
+        std::process::exit(1);
+    }
+    println!(
+        "Vacuumed {}: {} KiB -> {} KiB",
+        CostTracker::db_path().display(),
+        before / 1024,
+        db_size() / 1024
+    );
+}
+
+fn cmd_db_migrate() {
+    let Some(mut tracker) = open_history_db() else {
+        return;
+    };
+    let path = CostTracker::db_path();
+    match tracker.schema_version() {
+        Ok(version) if version > SCHEMA_VERSION => {
+            eprintln!(
+                "{} is at schema version {version}, newer than this build supports ({SCHEMA_VERSION})",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Error reading schema version: {e}");
+            std::process::exit(1);
+        }
+    }
+    match tracker.migrate() {
+        Ok((from, to)) if from == to => {
+            println!("{} is up to date (version {to})", path.display())
+        }
+        Ok((from, to)) => println!("Migrated {} from version {from} to {to}", path.display()),
+        Err(e) => {
+            eprintln!("Error migrating {}: {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
 fn cmd_preset(name: &str) {
     let config = match name {
         "minimal" => preset_minimal(),
@@ -285,6 +540,8 @@ fn widget(widget_type: &str) -> LineWidgetConfig {
         padding: None,
         merge_next: false,
         metadata: HashMap::new(),
+        thresholds: HashMap::new(),
+        when: None,
     }
 }
 
@@ -464,7 +721,37 @@ fn cmd_license_status() {
     }
 }
 
-fn cmd_stats(period: &str) {
+struct StatsArgs {
+    period: String,
+    format: String,
+    from: Option<String>,
+    to: Option<String>,
+    group_by: Option<String>,
+    weekly_limit: f64,
+    config: Option<String>,
+}
+
+fn cmd_stats(args: StatsArgs) {
+    if !matches!(args.format.as_str(), "text" | "json" | "csv" | "table") {
+        eprintln!(
+            "Unknown format '{}'. Use text, json, csv or table.",
+            args.format
+        );
+        std::process::exit(1);
+    }
+    let group_by = match args.group_by.as_deref().map(GroupBy::parse) {
+        Some(None) => {
+            eprintln!(
+                "Unknown group '{}'. Use day, model or project.",
+                args.group_by.unwrap_or_default()
+            );
+            std::process::exit(1);
+        }
+        Some(Some(g)) => Some(g),
+        None => None,
+    };
+    let period = args.period.as_str();
+
     if !claude_status::license::is_pro() {
         println!("claude-status Stats (Pro feature)");
         println!("=================================");
@@ -476,34 +763,65 @@ fn cmd_stats(period: &str) {
         return;
     }
 
-    let tracker = match claude_status::CostTracker::open() {
+    let tracker = match CostTracker::open() {
         Ok(t) => t,
         Err(e) => {
             eprintln!("Error opening cost database: {e}");
-            return;
+            std::process::exit(1);
         }
     };
 
+    // Days, weeks and months as set in `[calendar]`
+    let calendar = Config::load(args.config.as_deref()).calendar;
+    let tz = calendar.timezone;
     let now = chrono::Utc::now();
-    let today_start = now
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc()
-        .timestamp();
-    let yesterday_start = today_start - 86400;
-    let week_start = today_start
-        - (now.weekday().num_days_from_monday() as i64 * 86400);
-    let month_start = now
-        .date_naive()
-        .with_day(1)
-        .unwrap()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        .and_utc()
-        .timestamp();
+    let today_start = calendar.start(Period::Day, now);
+    let yesterday_start = tz.midnight(tz.date(now) - chrono::Days::new(1));
+    let week_start = calendar.start(Period::Week, now);
+    let month_start = calendar.start(Period::Month, now);
     let now_ts = now.timestamp();
 
+    let grouped = args.format != "text"
+        || group_by.is_some()
+        || args.from.is_some()
+        || args.to.is_some();
+    if grouped {
+        let period_start = match period {
+            "daily" => today_start,
+            "monthly" => month_start,
+            _ => week_start,
+        };
+        let from = match args.from.as_deref().map(parse_day) {
+            Some(Ok(day)) => tz.midnight(day),
+            Some(Err(e)) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            None => period_start,
+        };
+        let to = match args.to.as_deref().map(parse_day) {
+            Some(Ok(day)) => tz.midnight(day + chrono::Days::new(1)),
+            Some(Err(e)) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+            None => now_ts,
+        };
+        if from >= to {
+            eprintln!("--from must be before --to");
+            std::process::exit(1);
+        }
+        let group_by = group_by.unwrap_or(GroupBy::Day);
+        let groups = tracker.cost_grouped(from, to, group_by, tz);
+        let report = match args.format.as_str() {
+            "json" => stats_json(from, to, group_by, tz, &groups),
+            "csv" => stats_csv(group_by, &groups),
+            _ => stats_table(group_by, &groups),
+        };
+        print!("{report}");
+        return;
+    }
+
     println!("claude-status Stats");
     println!("===================");
     println!();
@@ -528,7 +846,7 @@ fn cmd_stats(period: &str) {
 
     // Weekly
     let weekly_cost = tracker.session_cost_range(week_start, now_ts);
-    let weekly_limit = 200.0;
+    let weekly_limit = args.weekly_limit;
     let weekly_pct = (weekly_cost / weekly_limit) * 100.0;
     println!(
         "  Weekly:  ${:.2} ({:.0}% of ${:.0} limit)",
@@ -555,9 +873,7 @@ fn cmd_stats(period: &str) {
         println!();
         println!("  Top costly sessions ({period}):");
         for (i, session) in top.iter().enumerate() {
-            let dt = chrono::DateTime::from_timestamp(session.start_time, 0)
-                .map(|d| d.format("%b %d, %H:%M").to_string())
-                .unwrap_or_else(|| "unknown".into());
+            let dt = tz.format(session.start_time, "%b %d, %H:%M");
             println!(
                 "  {}. {} - ${:.2} ({})",
                 i + 1,
@@ -573,6 +889,113 @@ fn cmd_stats(period: &str) {
     println!("  Sessions this {period}: {session_count}");
 }
 
+/// Parse a `YYYY-MM-DD` date.
+fn parse_day(s: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{s}'. Use YYYY-MM-DD."))
+}
+
+fn stats_json(
+    from: i64,
+    to: i64,
+    group_by: GroupBy,
+    tz: Timezone,
+    groups: &[CostGroup],
+) -> String {
+    let day = |ts: i64| tz.format(ts, "%Y-%m-%d");
+    let report = serde_json::json!({
+        "from": day(from),
+        "to": day(to - 1),
+        "group_by": group_by.as_str(),
+        "total_cost": groups.iter().map(|g| g.cost).sum::<f64>(),
+        "groups": groups,
+    });
+    format!("{}\n", serde_json::to_string_pretty(&report).unwrap_or_default())
+}
+
+fn stats_csv(group_by: GroupBy, groups: &[CostGroup]) -> String {
+    let mut out = format!(
+        "{},sessions,cost_usd,tokens_input,tokens_output,tokens_cached\n",
+        group_by.as_str()
+    );
+    for g in groups {
+        let key = if g.key.contains([',', '"', '\n']) {
+            format!("\"{}\"", g.key.replace('"', "\"\""))
+        } else {
+            g.key.clone()
+        };
+        out.push_str(&format!(
+            "{},{},{:.4},{},{},{}\n",
+            key, g.sessions, g.cost, g.tokens_input, g.tokens_output, g.tokens_cached
+        ));
+    }
+    out
+}
+
+fn stats_table(group_by: GroupBy, groups: &[CostGroup]) -> String {
+    let total = CostGroup {
+        key: "Total".into(),
+        sessions: groups.iter().map(|g| g.sessions).sum(),
+        cost: groups.iter().map(|g| g.cost).sum(),
+        tokens_input: groups.iter().map(|g| g.tokens_input).sum(),
+        tokens_output: groups.iter().map(|g| g.tokens_output).sum(),
+        tokens_cached: groups.iter().map(|g| g.tokens_cached).sum(),
+    };
+    let row = |g: &CostGroup| {
+        [
+            g.key.clone(),
+            g.sessions.to_string(),
+            format!("${:.2}", g.cost),
+            g.tokens_input.to_string(),
+            g.tokens_output.to_string(),
+        ]
+    };
+    let header = [
+        group_by.as_str().to_string(),
+        "Sessions".into(),
+        "Cost".into(),
+        "Input".into(),
+        "Output".into(),
+    ];
+    let body: Vec<[String; 5]> = groups.iter().map(row).collect();
+    let footer = row(&total);
+
+    let mut widths = [0usize; 5];
+    for r in std::iter::once(&header).chain(&body).chain(std::iter::once(&footer)) {
+        for (w, cell) in widths.iter_mut().zip(r) {
+            *w = (*w).max(unicode_width::UnicodeWidthStr::width(cell.as_str()));
+        }
+    }
+    let line = |r: &[String; 5]| {
+        let cells: Vec<String> = r
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(i, (cell, w))| {
+                if i == 0 {
+                    format!("{cell:<w$}")
+                } else {
+                    format!("{cell:>w$}")
+                }
+            })
+            .collect();
+        format!("{}\n", cells.join("  ").trim_end())
+    };
+    let rule = format!(
+        "{}\n",
+        "-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1))
+    );
+
+    let mut out = line(&header);
+    out.push_str(&rule);
+    for r in &body {
+        out.push_str(&line(r));
+    }
+    out.push_str(&rule);
+    out.push_str(&line(&footer));
+    out
+}
+
 fn cmd_dump_schema() {
     let sample = serde_json::json!({
         "cwd": "/home/user/project",
@@ -621,3 +1044,75 @@ fn cmd_dump_schema() {
 
     println!("{}", serde_json::to_string_pretty(&sample).unwrap());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups() -> Vec<CostGroup> {
+        let group = |key: &str, sessions, cost, tokens_input| CostGroup {
+            key: key.into(),
+            sessions,
+            cost,
+            tokens_input,
+            tokens_output: 10,
+            tokens_cached: 0,
+        };
+        vec![
+            group("/work/acme", 2, 1.5, 100),
+            group("/work/acme, inc", 1, 0.25, 2000),
+            group("/work/\"quoted\"", 3, 12.0, 5),
+        ]
+    }
+
+    #[test]
+    fn test_stats_json_reports_range_and_total() {
+        let json = stats_json(
+            86_400,
+            3 * 86_400,
+            GroupBy::Project,
+            Timezone::Utc,
+            &groups(),
+        );
+        let report: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(report["from"], "1970-01-02");
+        assert_eq!(report["to"], "1970-01-03");
+        assert_eq!(report["group_by"], "project");
+        assert_eq!(report["total_cost"], 13.75);
+        assert_eq!(report["groups"][1]["key"], "/work/acme, inc");
+        assert_eq!(report["groups"][2]["sessions"], 3);
+    }
+
+    #[test]
+    fn test_stats_csv_quotes_commas_and_quotes() {
+        let csv = stats_csv(GroupBy::Project, &groups());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "project,sessions,cost_usd,tokens_input,tokens_output,tokens_cached",
+                "/work/acme,2,1.5000,100,10,0",
+                "\"/work/acme, inc\",1,0.2500,2000,10,0",
+                "\"/work/\"\"quoted\"\"\",3,12.0000,5,10,0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stats_table_aligns_columns_and_totals() {
+        let table = stats_table(GroupBy::Project, &groups());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "project          Sessions    Cost  Input  Output",
+                "------------------------------------------------",
+                "/work/acme              2   $1.50    100      10",
+                "/work/acme, inc         1   $0.25   2000      10",
+                "/work/\"quoted\"          3  $12.00      5      10",
+                "------------------------------------------------",
+                "Total                   6  $13.75   2105      30",
+            ]
+        );
+    }
+}
@@ -3,7 +3,16 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
-use crate::widgets::WidgetConfig;
+use crate::budget::Budget;
+use crate::calendar::Calendar;
+use crate::pricing::ModelRates;
+use crate::render::Background;
+use crate::themes::UserTheme;
+use crate::widgets::{WidgetConfig, WidgetRegistry};
+
+mod validate;
+
+pub use validate::ConfigError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -11,6 +20,17 @@ pub struct Config {
     pub lines: Vec<Vec<LineWidgetConfig>>,
     #[serde(default = "default_theme")]
     pub theme: String,
+    /// Themes for light and dark terminals when `theme = "auto"`.
+    #[serde(
+        default = "default_auto_light",
+        skip_serializing_if = "is_default_auto_light"
+    )]
+    pub auto_light: String,
+    #[serde(
+        default = "default_auto_dark",
+        skip_serializing_if = "is_default_auto_dark"
+    )]
+    pub auto_dark: String,
     #[serde(default)]
     pub powerline: PowerlineConfig,
     #[serde(default = "default_color_level")]
@@ -27,6 +47,17 @@ pub struct Config {
     pub inherit_separator_colors: bool,
     #[serde(default = "default_separator")]
     pub default_separator: String,
+    /// Per-model token prices overriding the built-in table, keyed by a
+    /// fragment of the model id (e.g. `opus`, `sonnet-4-5`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub pricing: HashMap<String, ModelRates>,
+    /// Spending limits keyed by a glob over project directories, e.g.
+    /// `"~/work/*" = { daily = 20, monthly = 300 }`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub budgets: HashMap<String, Budget>,
+    /// Time zone, week start and billing day that periods reset on.
+    #[serde(default, skip_serializing_if = "Calendar::is_default")]
+    pub calendar: Calendar,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +76,13 @@ pub struct LineWidgetConfig {
     pub merge_next: bool,
     #[serde(default)]
     pub metadata: HashMap<String, String>,
+    /// Colors keyed by the value at which they take over, e.g.
+    /// `{ yellow = 1.0, red = 5.0 }`; applies to widgets that expose a value.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub thresholds: HashMap<String, f64>,
+    /// Visibility rule such as `context_window.used_percentage > 70`; the
+    /// widget is skipped unless it holds. See `layout::condition`.
+    pub when: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,6 +126,8 @@ fn default_lines() -> Vec<Vec<LineWidgetConfig>> {
             padding: None,
             merge_next: false,
             metadata: HashMap::new(),
+            thresholds: HashMap::new(),
+            when: None,
         },
         LineWidgetConfig {
             widget_type: "context-percentage".into(),
@@ -99,6 +139,8 @@ fn default_lines() -> Vec<Vec<LineWidgetConfig>> {
             padding: None,
             merge_next: false,
             metadata: HashMap::new(),
+            thresholds: HashMap::new(),
+            when: None,
         },
         LineWidgetConfig {
             widget_type: "session-cost".into(),
@@ -110,6 +152,8 @@ fn default_lines() -> Vec<Vec<LineWidgetConfig>> {
             padding: None,
             merge_next: false,
             metadata: HashMap::new(),
+            thresholds: HashMap::new(),
+            when: None,
         },
         LineWidgetConfig {
             widget_type: "session-duration".into(),
@@ -121,6 +165,8 @@ fn default_lines() -> Vec<Vec<LineWidgetConfig>> {
             padding: None,
             merge_next: false,
             metadata: HashMap::new(),
+            thresholds: HashMap::new(),
+            when: None,
         },
     ]]
 }
@@ -128,6 +174,18 @@ fn default_lines() -> Vec<Vec<LineWidgetConfig>> {
 fn default_theme() -> String {
     "default".into()
 }
+fn default_auto_light() -> String {
+    "light".into()
+}
+fn default_auto_dark() -> String {
+    "default".into()
+}
+fn is_default_auto_light(theme: &str) -> bool {
+    theme == default_auto_light()
+}
+fn is_default_auto_dark(theme: &str) -> bool {
+    theme == default_auto_dark()
+}
 fn default_color_level() -> String {
     "auto".into()
 }
@@ -160,6 +218,58 @@ impl Config {
         }
     }
 
+    /// Like `load`, but reports why the file could not be used instead of
+    /// falling back to the defaults: unreadable files, TOML syntax errors,
+    /// and unknown widget types, themes, colors or metadata keys.
+    /// A missing default config is not an error.
+    pub fn load_checked(
+        path: Option<&str>,
+        registry: &WidgetRegistry,
+        user_themes: &[UserTheme],
+    ) -> Result<Self, Vec<ConfigError>> {
+        let config_path = match path {
+            Some(p) => PathBuf::from(p),
+            None => match Self::default_path() {
+                Some(p) if p.exists() => p,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let contents = std::fs::read_to_string(&config_path).map_err(|e| {
+            vec![ConfigError::new(format!(
+                "cannot read {}: {e}",
+                config_path.display()
+            ))]
+        })?;
+        Self::parse_checked(&contents, registry, user_themes)
+    }
+
+    /// Parse and validate config source text. Widget types are looked up in
+    /// `registry`; themes among the built-ins and `user_themes`.
+    pub fn parse_checked(
+        source: &str,
+        registry: &WidgetRegistry,
+        user_themes: &[UserTheme],
+    ) -> Result<Self, Vec<ConfigError>> {
+        let config: Self =
+            toml::from_str(source).map_err(|e| vec![ConfigError::from_toml(source, &e)])?;
+        let errors = validate::validate(source, registry, user_themes);
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The theme to render with: `theme`, or for `theme = "auto"` the
+    /// light or dark one to suit the terminal, dark when it's unknown.
+    pub fn theme_name(&self, background: Option<Background>) -> &str {
+        match (self.theme.as_str(), background) {
+            ("auto", Some(Background::Light)) => &self.auto_light,
+            ("auto", _) => &self.auto_dark,
+            (theme, _) => theme,
+        }
+    }
+
     pub fn default_path() -> Option<PathBuf> {
         // Check CLAUDE_CONFIG_DIR first
         if let Ok(dir) = std::env::var("CLAUDE_CONFIG_DIR") {
@@ -187,6 +297,7 @@ impl Config {
             padding: lwc.padding.clone(),
             merge_next: lwc.merge_next,
             metadata: lwc.metadata.clone(),
+            compact: false,
         }
     }
 }
@@ -196,6 +307,8 @@ impl Default for Config {
         Self {
             lines: default_lines(),
             theme: default_theme(),
+            auto_light: default_auto_light(),
+            auto_dark: default_auto_dark(),
             powerline: PowerlineConfig::default(),
             color_level: default_color_level(),
             default_padding: default_padding(),
@@ -204,6 +317,9 @@ impl Default for Config {
             global_bold: false,
             inherit_separator_colors: false,
             default_separator: default_separator(),
+            pricing: HashMap::new(),
+            budgets: HashMap::new(),
+            calendar: Calendar::default(),
         }
     }
 }
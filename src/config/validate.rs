@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::layout::condition::Condition;
+use crate::render::Renderer;
+use crate::themes::{Theme, UserTheme};
+use crate::widgets::WidgetRegistry;
+
+/// A problem in the config file, with its 1-based position when known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl ConfigError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            line: None,
+            column: None,
+        }
+    }
+
+    fn at(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self {
+            message: message.into(),
+            line: Some(before.matches('\n').count() + 1),
+            column: Some(before[line_start..].chars().count() + 1),
+        }
+    }
+
+    pub(crate) fn from_toml(source: &str, err: &toml::de::Error) -> Self {
+        let message = err.message().trim().to_string();
+        match err.span() {
+            Some(span) => Self::at(source, span.start, message),
+            None => Self::new(message),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{line}:{column}: {}", self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The parts of the config that get checked, with source positions.
+#[derive(Deserialize)]
+struct SpannedConfig {
+    theme: Option<Spanned<String>>,
+    auto_light: Option<Spanned<String>>,
+    auto_dark: Option<Spanned<String>>,
+    #[serde(default)]
+    lines: Vec<Vec<SpannedWidget>>,
+}
+
+#[derive(Deserialize)]
+struct SpannedWidget {
+    #[serde(rename = "type")]
+    widget_type: Spanned<String>,
+    color: Option<Spanned<String>>,
+    background_color: Option<Spanned<String>>,
+    #[serde(default)]
+    metadata: HashMap<String, Spanned<String>>,
+    #[serde(default)]
+    thresholds: HashMap<String, Spanned<f64>>,
+    when: Option<Spanned<String>>,
+}
+
+/// Check an already-parsed config source for unknown widgets, themes,
+/// colors and metadata keys, against the widgets in `registry` and the
+/// built-in themes plus `user_themes`. Errors are ordered by position.
+pub(crate) fn validate(
+    source: &str,
+    registry: &WidgetRegistry,
+    user_themes: &[UserTheme],
+) -> Vec<ConfigError> {
+    let spanned: SpannedConfig = match toml::from_str(source) {
+        Ok(s) => s,
+        Err(e) => return vec![ConfigError::from_toml(source, &e)],
+    };
+    let mut errors = Vec::new();
+
+    // `auto` stands for `auto_light` or `auto_dark`, which are checked below
+    if let Some(theme) = spanned.theme.as_ref().filter(|t| t.get_ref() != "auto") {
+        check_theme(source, theme, user_themes, &mut errors);
+    }
+    for theme in [&spanned.auto_light, &spanned.auto_dark]
+        .into_iter()
+        .flatten()
+    {
+        check_theme(source, theme, user_themes, &mut errors);
+    }
+
+    for widget in spanned.lines.iter().flatten() {
+        let widget_type = widget.widget_type.get_ref();
+        if !registry.contains(widget_type) {
+            errors.push(ConfigError::at(
+                source,
+                widget.widget_type.span().start,
+                format!("unknown widget type \"{widget_type}\""),
+            ));
+            continue;
+        }
+
+        for color in [&widget.color, &widget.background_color]
+            .into_iter()
+            .flatten()
+        {
+            if Renderer::try_parse_color(color.get_ref()).is_none() {
+                errors.push(ConfigError::at(
+                    source,
+                    color.span().start,
+                    format!(
+                        "invalid color \"{}\" on widget \"{widget_type}\"",
+                        color.get_ref()
+                    ),
+                ));
+            }
+        }
+
+        for (color, limit) in &widget.thresholds {
+            if Renderer::try_parse_color(color).is_none() {
+                errors.push(ConfigError::at(
+                    source,
+                    key_offset(source, color, limit.span()),
+                    format!("invalid threshold color \"{color}\" on widget \"{widget_type}\""),
+                ));
+            }
+        }
+
+        if let Some(when) = &widget.when
+            && let Err(e) = Condition::parse(when.get_ref())
+        {
+            errors.push(ConfigError::at(
+                source,
+                when.span().start,
+                format!("invalid `when` rule on widget \"{widget_type}\": {e}"),
+            ));
+        }
+
+        for (key, value) in &widget.metadata {
+            if registry.accepts_metadata(widget_type, key) == Some(false) {
+                errors.push(ConfigError::at(
+                    source,
+                    key_offset(source, key, value.span()),
+                    format!("unknown metadata key \"{key}\" for widget \"{widget_type}\""),
+                ));
+            }
+        }
+    }
+
+    errors.sort_by_key(|e| (e.line, e.column));
+    errors
+}
+
+/// Report an unknown theme name, or the problems in a user theme file.
+fn check_theme(
+    source: &str,
+    theme: &Spanned<String>,
+    user_themes: &[UserTheme],
+    errors: &mut Vec<ConfigError>,
+) {
+    let name = theme.get_ref();
+    if let Some(user) = user_themes.iter().find(|t| &t.theme.name == name) {
+        for e in &user.errors {
+            errors.push(ConfigError::at(
+                source,
+                theme.span().start,
+                format!("theme \"{name}\" ({}): {e}", user.path.display()),
+            ));
+        }
+    } else if !Theme::list().contains(&name.as_str()) {
+        let mut available = Theme::list();
+        for user in user_themes {
+            if !available.contains(&user.theme.name.as_str()) {
+                available.push(&user.theme.name);
+            }
+        }
+        errors.push(ConfigError::at(
+            source,
+            theme.span().start,
+            format!(
+                "unknown theme \"{name}\" (available: {})",
+                available.join(", ")
+            ),
+        ));
+    }
+}
+
+/// Position of a metadata key, found by looking back from its value on the
+/// same line; falls back to the value itself.
+fn key_offset(source: &str, key: &str, value: Range<usize>) -> usize {
+    let line_start = source[..value.start]
+        .rfind('\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    source[line_start..value.start]
+        .rfind(key)
+        .map(|i| line_start + i)
+        .unwrap_or(value.start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(source: &str) -> Vec<String> {
+        validate(source, &WidgetRegistry::builtin(), &[])
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_config_has_no_errors() {
+        let source = r##"
+theme = "nord"
+lines = [[
+  { type = "cwd", color = "#88c0d0", metadata = { segments = "2" } },
+  { type = "model", background_color = "236" },
+  { type = "session-cost", thresholds = { yellow = 1.0, "#ff0000" = 5 } },
+]]
+"##;
+        assert!(check(source).is_empty());
+    }
+
+    #[test]
+    fn test_reports_each_problem_with_position() {
+        let source = r#"theme = "nrod"
+lines = [[
+  { type = "modle" },
+  { type = "cwd", color = "purple-ish", metadata = { segmnets = "2" } },
+  { type = "model", when = "model.id contains" },
+  { type = "session-cost", thresholds = { amber = 1.0 } },
+]]
+"#;
+        assert_eq!(
+            check(source),
+            vec![
+                "1:9: unknown theme \"nrod\" (available: default, solarized, nord, dracula, gruvbox, monokai, light, high-contrast, one-dark, tokyo-night, catppuccin)",
+                "3:12: unknown widget type \"modle\"",
+                "4:27: invalid color \"purple-ish\" on widget \"cwd\"",
+                "4:54: unknown metadata key \"segmnets\" for widget \"cwd\"",
+                "5:28: invalid `when` rule on widget \"model\": unexpected end of expression",
+                "6:43: invalid threshold color \"amber\" on widget \"session-cost\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_theme_checks_its_variants() {
+        assert!(check("theme = \"auto\"\nauto_dark = \"nord\"\n").is_empty());
+        let errors = check("theme = \"auto\"\nauto_light = \"solarised\"\n");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("2:14: unknown theme \"solarised\""));
+    }
+
+    #[test]
+    fn test_user_themes_are_known_and_checked() {
+        let ocean = UserTheme::parse("ocean", "inherits = \"nord\"\n");
+        let broken = UserTheme::parse("broken", "inherits = \"nowhere\"\n");
+        assert!(!broken.errors.is_empty());
+        let themes = [ocean, broken];
+        let check = |source: &str| validate(source, &WidgetRegistry::builtin(), &themes);
+
+        assert!(check("theme = \"ocean\"\n").is_empty());
+        let errors = check("theme = \"broken\"\n");
+        assert_eq!(errors.len(), themes[1].errors.len());
+        assert!(errors[0].message.starts_with("theme \"broken\""));
+        let errors = check("theme = \"oceans\"\n");
+        assert!(errors[0].message.ends_with("catppuccin, ocean, broken)"));
+    }
+
+    #[test]
+    fn test_parse_error_points_at_offending_line() {
+        let errors = validate(
+            "theme = \"nord\"\nlines = [[ { type = } ]]\n",
+            &WidgetRegistry::builtin(),
+            &[],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, Some(2));
+    }
+}
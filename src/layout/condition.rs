@@ -0,0 +1,404 @@
+use std::cell::OnceCell;
+
+use serde_json::{Value, json};
+
+use crate::git::GitContext;
+use crate::widgets::SessionData;
+
+/// A parsed `when = "..."` visibility rule, e.g.
+/// `context_window.used_percentage > 70` or
+/// `model.id contains "opus" && !git.dirty`.
+///
+/// Paths name fields of the status line JSON input; `git.*` names fields of
+/// the shared git snapshot plus `git.dirty`. Missing fields are null, which
+/// is falsy and never compares greater or less than anything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Path(String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(String),
+    Literal(Value),
+    Op(Op),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Self(expr)),
+            Some(t) => Err(format!("unexpected {} after expression", describe(t))),
+        }
+    }
+
+    pub fn eval(&self, facts: &Facts) -> bool {
+        truthy(&eval(&self.0, facts))
+    }
+}
+
+/// The values a rule can look at for one render. The session JSON is built
+/// on first use and shared by every rule on every line.
+pub struct Facts<'a> {
+    data: &'a SessionData,
+    git: &'a GitContext,
+    session: OnceCell<Value>,
+}
+
+impl<'a> Facts<'a> {
+    pub fn new(data: &'a SessionData, git: &'a GitContext) -> Self {
+        Self {
+            data,
+            git,
+            session: OnceCell::new(),
+        }
+    }
+
+    /// Value at a dotted path such as `model.id` or `git.branch`; null when
+    /// any part is missing.
+    pub fn lookup(&self, path: &str) -> Value {
+        let mut parts = path.split('.');
+        let root = match parts.next() {
+            Some("git") => self.git_value(),
+            Some(first) => self
+                .session
+                .get_or_init(|| serde_json::to_value(self.data).unwrap_or(Value::Null))
+                .get(first)
+                .cloned()
+                .unwrap_or(Value::Null),
+            None => Value::Null,
+        };
+        parts.fold(root, |value, key| {
+            value.get(key).cloned().unwrap_or(Value::Null)
+        })
+    }
+
+    fn git_value(&self) -> Value {
+        let Some(snap) = self.git.snapshot(self.data) else {
+            return Value::Null;
+        };
+        let dirty =
+            snap.staged + snap.modified + snap.renamed + snap.untracked + snap.conflicts > 0;
+        json!({
+            "branch": snap.branch,
+            "head": snap.head,
+            "upstream": snap.upstream,
+            "ahead": snap.ahead,
+            "behind": snap.behind,
+            "stash": snap.stash,
+            "staged": snap.staged,
+            "modified": snap.modified,
+            "renamed": snap.renamed,
+            "untracked": snap.untracked,
+            "conflicts": snap.conflicts,
+            "worktree": snap.worktree,
+            "dirty": dirty,
+        })
+    }
+}
+
+fn eval(expr: &Expr, facts: &Facts) -> Value {
+    match expr {
+        Expr::Literal(v) => v.clone(),
+        Expr::Path(p) => facts.lookup(p),
+        Expr::Not(e) => Value::Bool(!truthy(&eval(e, facts))),
+        Expr::And(a, b) => Value::Bool(truthy(&eval(a, facts)) && truthy(&eval(b, facts))),
+        Expr::Or(a, b) => Value::Bool(truthy(&eval(a, facts)) || truthy(&eval(b, facts))),
+        Expr::Compare(a, op, b) => Value::Bool(compare(&eval(a, facts), *op, &eval(b, facts))),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+fn compare(left: &Value, op: Op, right: &Value) -> bool {
+    if op == Op::Contains {
+        return match (left, right) {
+            (Value::String(l), Value::String(r)) => l.contains(r.as_str()),
+            (Value::Array(items), r) => items.contains(r),
+            _ => false,
+        };
+    }
+
+    let ordering = match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64().partial_cmp(&r.as_f64()),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        (l, r) if matches!(op, Op::Eq | Op::Ne) => return (l == r) == (op == Op::Eq),
+        _ => None,
+    };
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        Op::Eq => ordering.is_eq(),
+        Op::Ne => ordering.is_ne(),
+        Op::Gt => ordering.is_gt(),
+        Op::Ge => ordering.is_ge(),
+        Op::Lt => ordering.is_lt(),
+        Op::Le => ordering.is_le(),
+        Op::Contains => unreachable!(),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' if next == Some('=') => Token::Op(Op::Eq),
+            '!' if next == Some('=') => Token::Op(Op::Ne),
+            '>' if next == Some('=') => Token::Op(Op::Ge),
+            '<' if next == Some('=') => Token::Op(Op::Le),
+            '&' if next == Some('&') => Token::And,
+            '|' if next == Some('|') => Token::Or,
+            '>' => Token::Op(Op::Gt),
+            '<' => Token::Op(Op::Lt),
+            '!' => Token::Not,
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or("unterminated string")?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                tokens.push(Token::Literal(Value::String(text)));
+                i += end + 2;
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let len = 1 + chars[i + 1..]
+                    .iter()
+                    .take_while(|ch| ch.is_ascii_digit() || **ch == '.')
+                    .count();
+                let text: String = chars[i..i + len].iter().collect();
+                let number: f64 = text.parse().map_err(|_| format!("invalid number {text}"))?;
+                tokens.push(Token::Literal(json!(number)));
+                i += len;
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|ch| ch.is_ascii_alphanumeric() || **ch == '_' || **ch == '.')
+                    .count();
+                let word: String = chars[i..i + len].iter().collect();
+                tokens.push(match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "contains" => Token::Op(Op::Contains),
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    _ => Token::Path(word),
+                });
+                i += len;
+                continue;
+            }
+            c => return Err(format!("unexpected character '{c}'")),
+        };
+        // Two-character operators consume both.
+        i += match token {
+            Token::Op(Op::Eq | Op::Ne | Op::Ge | Op::Le) | Token::And | Token::Or => 2,
+            _ => 1,
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Path(p) => format!("'{p}'"),
+        Token::Literal(v) => v.to_string(),
+        Token::Op(op) => format!("{op:?}").to_lowercase(),
+        Token::Not => "'!'".into(),
+        Token::And => "'&&'".into(),
+        Token::Or => "'||'".into(),
+        Token::Open => "'('".into(),
+        Token::Close => "')'".into(),
+    }
+}
+
+/// Recursive descent, loosest binding first: or, and, not, comparison.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.operand()?;
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let right = self.operand()?;
+            return Ok(Expr::Compare(Box::new(left), op, Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Path(p)) => Ok(Expr::Path(p)),
+            Some(Token::Literal(v)) => Ok(Expr::Literal(v)),
+            Some(Token::Open) => {
+                let inner = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(inner)
+                    }
+                    _ => Err("missing ')'".into()),
+                }
+            }
+            Some(t) => Err(format!("expected a field or value, found {}", describe(&t))),
+            None => Err("unexpected end of expression".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::{ContextWindow, Model};
+
+    fn data() -> SessionData {
+        SessionData {
+            model: Some(Model {
+                id: Some("claude-opus-4-6".into()),
+                display_name: Some("Opus".into()),
+            }),
+            context_window: Some(ContextWindow {
+                used_percentage: Some(82.5),
+                ..Default::default()
+            }),
+            exceeds_200k_tokens: Some(false),
+            ..Default::default()
+        }
+    }
+
+    fn check(rule: &str) -> bool {
+        let data = data();
+        let git = GitContext::default();
+        Condition::parse(rule)
+            .unwrap()
+            .eval(&Facts::new(&data, &git))
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(check("context_window.used_percentage > 70"));
+        assert!(!check("context_window.used_percentage <= 80"));
+        assert!(check("model.id contains \"opus\""));
+        assert!(check("model.display_name == 'Opus'"));
+        assert!(check("model.display_name != \"Sonnet\""));
+    }
+
+    #[test]
+    fn test_boolean_logic_and_precedence() {
+        assert!(check("exceeds_200k_tokens || model.id contains 'opus'"));
+        assert!(!check("exceeds_200k_tokens or model.id contains 'haiku'"));
+        assert!(check(
+            "!exceeds_200k_tokens && context_window.used_percentage > 50"
+        ));
+        assert!(!check(
+            "not (context_window.used_percentage > 50 and model.id)"
+        ));
+    }
+
+    #[test]
+    fn test_missing_fields_are_falsy() {
+        assert!(!check("cost.total_cost_usd > 1"));
+        assert!(!check("cost.total_cost_usd < 1"));
+        assert!(check("cost.total_cost_usd == null"));
+        // Outside a git repository
+        assert!(!check("git.dirty"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Condition::parse("model.id ==").is_err());
+        assert!(Condition::parse("(git.dirty").is_err());
+        assert!(Condition::parse("model.id contains \"opus").is_err());
+        assert!(Condition::parse("a b").is_err());
+        assert!(Condition::parse("cost # 2").is_err());
+    }
+}
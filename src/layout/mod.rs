@@ -1,9 +1,20 @@
+use std::cmp::Reverse;
+use std::ops::Range;
+
 use unicode_width::UnicodeWidthStr;
 
-use crate::config::Config;
+use crate::config::{Config, LineWidgetConfig};
 use crate::render::Renderer;
 use crate::themes::Theme;
-use crate::widgets::{SessionData, WidgetOutput, WidgetRegistry};
+use crate::widgets::{RenderContext, SessionData, WidgetOutput, WidgetRegistry};
+
+pub mod condition;
+mod zones;
+
+use condition::{Condition, Facts};
+
+/// Powerline background for widgets without one of their own or from the theme.
+const DEFAULT_BG: &str = "black";
 
 pub struct LayoutEngine<'a> {
     config: &'a Config,
@@ -13,7 +24,7 @@ pub struct LayoutEngine<'a> {
 
 impl<'a> LayoutEngine<'a> {
     pub fn new(config: &'a Config, renderer: &'a Renderer) -> Self {
-        let theme = Theme::get(&config.theme);
+        let theme = Theme::load(config.theme_name(renderer.background));
         Self {
             config,
             renderer,
@@ -21,14 +32,24 @@ impl<'a> LayoutEngine<'a> {
         }
     }
 
-    pub fn render(
+    pub fn render(&self, data: &SessionData, registry: &WidgetRegistry) -> Vec<String> {
+        self.render_with_context(data, &RenderContext::new(), registry)
+    }
+
+    /// Like `render`, with the clock, license, history database, git state
+    /// and terminal size taken from `ctx`. Prices, budgets and the calendar
+    /// come from `ctx` where it has them, else from the config.
+    pub fn render_with_context(
         &self,
         data: &SessionData,
-        _config: &Config,
+        ctx: &RenderContext,
         registry: &WidgetRegistry,
     ) -> Vec<String> {
         let config = self.config;
-        let term_width = Self::terminal_width(config);
+        ctx.attach_config(config);
+        let term_width = Self::terminal_width(config, ctx);
+        let compact_only = term_width < config.compact_threshold as usize;
+        let facts = Facts::new(data, ctx.git());
         let mut output_lines = Vec::new();
 
         for line_config in &config.lines {
@@ -36,16 +57,32 @@ impl<'a> LayoutEngine<'a> {
                 continue;
             }
 
-            let mut widgets: Vec<(WidgetOutput, &crate::config::LineWidgetConfig)> = Vec::new();
-            for wc in line_config {
-                let widget_config = Config::to_widget_config(wc);
-                if let Some(output) = registry.render(&wc.widget_type, data, &widget_config)
-                    && output.visible
-                {
-                    widgets.push((output, wc));
+            // Lines split into left/center/right zones by `zone` markers
+            if let Some(zones) = zones::split(line_config) {
+                let render = |compact| {
+                    zones.each_ref().map(|zone| {
+                        let configs = zone.widgets.iter().copied();
+                        self.render_widgets(configs, data, registry, ctx, &facts, compact)
+                    })
+                };
+                let mut rendered = render(compact_only);
+                if !compact_only && self.zones_width(&zones, &rendered) > term_width {
+                    rendered = render(true);
                 }
+                output_lines.extend(self.layout_zones(&zones, rendered, term_width));
+                continue;
             }
 
+            // Full rendering first; if it overflows, retry compact before
+            // resorting to dropping widgets.
+            let mut widgets =
+                self.render_widgets(line_config, data, registry, ctx, &facts, compact_only);
+            if !compact_only && self.line_width(&widgets) > term_width {
+                widgets = self.render_widgets(line_config, data, registry, ctx, &facts, true);
+            }
+
+            self.fit_to_width(&mut widgets, term_width);
+
             if widgets.is_empty() {
                 continue;
             }
@@ -61,12 +98,12 @@ impl<'a> LayoutEngine<'a> {
         if config.powerline.enabled && config.powerline.auto_align && output_lines.len() > 1 {
             let max_display_width = output_lines
                 .iter()
-                .map(|l| UnicodeWidthStr::width(strip_ansi(l).as_str()))
+                .map(|l| display_width(l))
                 .max()
                 .unwrap_or(0);
 
             for line in &mut output_lines {
-                let current_width = UnicodeWidthStr::width(strip_ansi(line).as_str());
+                let current_width = display_width(line);
                 if current_width < max_display_width {
                     let pad = max_display_width - current_width;
                     line.push_str(&" ".repeat(pad));
@@ -77,13 +114,122 @@ impl<'a> LayoutEngine<'a> {
         output_lines
     }
 
+    fn render_widgets<'c>(
+        &self,
+        line_config: impl IntoIterator<Item = &'c LineWidgetConfig>,
+        data: &SessionData,
+        registry: &WidgetRegistry,
+        ctx: &RenderContext,
+        facts: &Facts,
+        compact: bool,
+    ) -> Vec<(WidgetOutput, &'c LineWidgetConfig)> {
+        let mut widgets = Vec::new();
+        for wc in line_config {
+            // A rule that fails to parse hides the widget; `validate` reports it.
+            if let Some(rule) = &wc.when
+                && !Condition::parse(rule).is_ok_and(|c| c.eval(facts))
+            {
+                continue;
+            }
+            let mut widget_config = Config::to_widget_config(wc);
+            widget_config.compact = compact;
+            if let Some(output) = registry.render(&wc.widget_type, data, ctx, &widget_config)
+                && output.visible
+            {
+                widgets.push((output, wc));
+            }
+        }
+        widgets
+    }
+
+    /// Drop the lowest-priority widgets, one at a time, until the line fits
+    /// `max_width`. Widgets chained with `merge_next` render as one unit and are
+    /// dropped together; separator widgets are structural and never chosen, but
+    /// are pruned when a drop leaves them dangling.
+    fn fit_to_width(&self, widgets: &mut Vec<(WidgetOutput, &LineWidgetConfig)>, max_width: usize) {
+        while self.line_width(widgets) > max_width {
+            // On equal priority, drop the rightmost group first.
+            let victim = merge_groups(widgets)
+                .into_iter()
+                .filter(|g| !g.clone().all(|i| is_structural(widgets[i].1)))
+                .min_by_key(|g| {
+                    let priority = g.clone().map(|i| widgets[i].0.priority).max();
+                    (priority, Reverse(g.start))
+                });
+
+            let Some(group) = victim else {
+                break;
+            };
+            let at = group.start;
+            widgets.drain(group);
+            prune_separators(widgets, at);
+        }
+    }
+
+    /// Display width of a line as `assemble_line` / `assemble_powerline_line`
+    /// would lay it out, excluding flex fill.
+    fn line_width(&self, widgets: &[(WidgetOutput, &LineWidgetConfig)]) -> usize {
+        let config = self.config;
+        let has_flex = widgets
+            .iter()
+            .any(|(_, wc)| wc.widget_type == "flex-separator");
+
+        let sep_width = if config.powerline.enabled {
+            UnicodeWidthStr::width(config.powerline.separator.as_str())
+        } else {
+            UnicodeWidthStr::width(config.default_separator.as_str())
+        };
+
+        let mut total = 0usize;
+        for (i, (output, wc)) in widgets.iter().enumerate() {
+            if wc.widget_type == "flex-separator" {
+                continue;
+            }
+            if i > 0 {
+                let prev = widgets[i - 1].1;
+                let flex_boundary = prev.widget_type == "flex-separator";
+                if !prev.merge_next && !flex_boundary {
+                    total += sep_width;
+                }
+            }
+            let padding = wc.padding.as_deref().unwrap_or(&config.default_padding);
+            total += output.display_width + UnicodeWidthStr::width(padding) * 2;
+        }
+
+        if config.powerline.enabled {
+            if let Some(ref cap) = config.powerline.start_cap {
+                total += UnicodeWidthStr::width(cap.as_str());
+            }
+            if let Some(ref cap) = config.powerline.end_cap {
+                total += UnicodeWidthStr::width(cap.as_str());
+            }
+            if has_flex {
+                // Closing separator of the left side plus the reverse separator
+                // opening the right side.
+                total += sep_width + 1;
+            }
+        }
+
+        total
+    }
+
     /// Resolve the foreground color for a widget using the priority chain:
-    /// explicit config color > widget color_hint > theme role > None
+    /// crossed threshold > explicit config color > widget color_hint > theme role > None
     fn resolve_fg_color(
         &self,
         wc: &crate::config::LineWidgetConfig,
         output: &WidgetOutput,
     ) -> Option<String> {
+        // 0. Highest threshold the widget's value has reached
+        if let Some(value) = output.value
+            && let Some((color, _)) = wc
+                .thresholds
+                .iter()
+                .filter(|(_, limit)| value >= **limit)
+                .max_by(|a, b| a.1.total_cmp(b.1))
+        {
+            return Some(color.clone());
+        }
         // 1. Explicit config color
         if let Some(ref color) = wc.color {
             return Some(color.clone());
@@ -206,7 +352,6 @@ impl<'a> LayoutEngine<'a> {
     ) -> String {
         let config = self.config;
         let pl_sep = &config.powerline.separator;
-        let default_bg = "black";
 
         // Check for flex-separator
         let has_flex = widgets
@@ -230,8 +375,8 @@ impl<'a> LayoutEngine<'a> {
         if let Some(ref cap) = config.powerline.start_cap {
             let first_bg = non_flex
                 .first()
-                .and_then(|(_, wc)| wc.background_color.as_deref())
-                .unwrap_or(default_bg);
+                .map(|(_, wc)| self.powerline_bg(wc))
+                .unwrap_or(DEFAULT_BG);
             let bg_spec = Renderer::parse_color(first_bg);
             parts.push(format!(
                 "{}{}{}",
@@ -271,16 +416,12 @@ impl<'a> LayoutEngine<'a> {
                 &mut parts,
                 &mut total_display_width,
                 max_width,
-                default_bg,
+                pl_sep,
             );
 
             // End left side with separator to reset
             if let Some(last_left) = left_widgets.last() {
-                let last_bg = last_left
-                    .1
-                    .background_color
-                    .as_deref()
-                    .unwrap_or(default_bg);
+                let last_bg = self.powerline_bg(last_left.1);
                 let last_bg_spec = Renderer::parse_color(last_bg);
                 parts.push(format!(
                     "{}{}{}",
@@ -317,8 +458,8 @@ impl<'a> LayoutEngine<'a> {
                 // Start with separator into first right widget
                 let first_bg = right_widgets
                     .first()
-                    .and_then(|(_, wc)| wc.background_color.as_deref())
-                    .unwrap_or(default_bg);
+                    .map(|(_, wc)| self.powerline_bg(wc))
+                    .unwrap_or(DEFAULT_BG);
                 let first_bg_spec = Renderer::parse_color(first_bg);
                 parts.push(format!(
                     "{}{}{}",
@@ -333,7 +474,7 @@ impl<'a> LayoutEngine<'a> {
                     &mut parts,
                     &mut total_display_width,
                     max_width,
-                    default_bg,
+                    pl_sep,
                 );
             }
         } else {
@@ -345,7 +486,7 @@ impl<'a> LayoutEngine<'a> {
                 &mut parts,
                 &mut total_display_width,
                 max_width,
-                default_bg,
+                pl_sep,
             );
         }
 
@@ -353,8 +494,8 @@ impl<'a> LayoutEngine<'a> {
         if let Some(ref cap) = config.powerline.end_cap {
             let last_bg = non_flex
                 .last()
-                .and_then(|(_, wc)| wc.background_color.as_deref())
-                .unwrap_or(default_bg);
+                .map(|(_, wc)| self.powerline_bg(wc))
+                .unwrap_or(DEFAULT_BG);
             let last_bg_spec = Renderer::parse_color(last_bg);
             parts.push(format!(
                 "{}{}{}",
@@ -374,24 +515,19 @@ impl<'a> LayoutEngine<'a> {
         parts: &mut Vec<String>,
         total_display_width: &mut usize,
         max_width: usize,
-        default_bg: &str,
+        pl_sep: &str,
     ) {
         let config = self.config;
-        let pl_sep = &config.powerline.separator;
 
         for (i, (output, wc)) in widgets.iter().enumerate() {
-            let this_bg = wc.background_color.as_deref().unwrap_or(default_bg);
+            let this_bg = self.powerline_bg(wc);
             let this_bg_spec = Renderer::parse_color(this_bg);
 
             if i > 0 && !widgets[i - 1].1.merge_next {
-                let prev_bg = widgets[i - 1]
-                    .1
-                    .background_color
-                    .as_deref()
-                    .unwrap_or(default_bg);
+                let prev_bg = self.powerline_bg(widgets[i - 1].1);
                 let prev_bg_spec = Renderer::parse_color(prev_bg);
 
-                let sep_width = UnicodeWidthStr::width(pl_sep.as_str());
+                let sep_width = UnicodeWidthStr::width(pl_sep);
                 if *total_display_width + sep_width + output.display_width > max_width {
                     break;
                 }
@@ -419,6 +555,15 @@ impl<'a> LayoutEngine<'a> {
         }
     }
 
+    /// Background of a powerline segment: the widget's own, then its theme
+    /// role's, then black.
+    fn powerline_bg<'w>(&'w self, wc: &'w LineWidgetConfig) -> &'w str {
+        wc.background_color
+            .as_deref()
+            .or_else(|| self.theme.background_for_widget(&wc.widget_type))
+            .unwrap_or(DEFAULT_BG)
+    }
+
     fn apply_style(
         &self,
         text: &str,
@@ -474,10 +619,8 @@ impl<'a> LayoutEngine<'a> {
         styled
     }
 
-    fn terminal_width(config: &Config) -> usize {
-        let width = crossterm::terminal::size()
-            .map(|(w, _)| w as usize)
-            .unwrap_or(120);
+    fn terminal_width(config: &Config, ctx: &RenderContext) -> usize {
+        let width = ctx.terminal_width().map_or(120, usize::from);
 
         match config.flex_mode.as_str() {
             "full" => width,
@@ -488,23 +631,75 @@ impl<'a> LayoutEngine<'a> {
     }
 }
 
+/// Split a line into runs of widgets joined by `merge_next`.
+fn merge_groups(widgets: &[(WidgetOutput, &LineWidgetConfig)]) -> Vec<Range<usize>> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for (i, (_, wc)) in widgets.iter().enumerate() {
+        if !wc.merge_next || i + 1 == widgets.len() {
+            groups.push(start..i + 1);
+            start = i + 1;
+        }
+    }
+    groups
+}
+
+fn is_structural(wc: &LineWidgetConfig) -> bool {
+    matches!(wc.widget_type.as_str(), "separator" | "flex-separator")
+}
+
+/// After removing widgets at index `at`, remove a separator widget that is now
+/// leading, trailing, or doubled up against another separator.
+fn prune_separators(widgets: &mut Vec<(WidgetOutput, &LineWidgetConfig)>, at: usize) {
+    let is_sep = |w: &(WidgetOutput, &LineWidgetConfig)| w.1.widget_type == "separator";
+
+    if at < widgets.len() && is_sep(&widgets[at]) && (at == 0 || is_sep(&widgets[at - 1])) {
+        widgets.remove(at);
+    } else if at > 0 && at == widgets.len() && is_sep(&widgets[at - 1]) {
+        widgets.remove(at - 1);
+    }
+}
+
+/// Terminal columns taken by `s` once printed: ANSI sequences take none,
+/// wide (CJK) characters and emoji take two, and emoji presentation and ZWJ
+/// sequences count as the single glyph a terminal draws.
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(strip_ansi(s).as_str())
+}
+
 /// Strip ANSI escape sequences from a string for display width calculation.
-fn strip_ansi(s: &str) -> String {
+/// Handles CSI sequences (colors) and OSC sequences (hyperlinks, titles),
+/// which end at BEL or ESC `\`.
+pub(crate) fn strip_ansi(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
-    let mut in_escape = false;
-    for ch in s.chars() {
-        if in_escape {
-            if ch.is_ascii_alphabetic() {
-                in_escape = false;
-            }
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            out.push(ch);
             continue;
         }
-        if ch == '\x1b' {
-            in_escape = true;
-            continue;
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two-character escapes (ESC c, ESC 7, ...)
+            _ => {}
         }
-        // Skip OSC sequences (\x1b]...\x07)
-        out.push(ch);
     }
     out
 }
@@ -0,0 +1,326 @@
+use std::cmp::Reverse;
+use std::ops::Range;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use super::{
+    LayoutEngine, display_width, is_structural, merge_groups, prune_separators, strip_ansi,
+};
+use crate::config::LineWidgetConfig;
+use crate::render::Renderer;
+use crate::widgets::WidgetOutput;
+
+/// Where a zone sits on the line; also its index in `[Zone; 3]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// What a zone gives up when the line is too wide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overflow {
+    /// Drop its lowest-priority widgets, one at a time.
+    Drop,
+    /// Disappear as a whole, at the priority of its most important widget.
+    Hide,
+    /// Cut text from its end, marked with an ellipsis.
+    Truncate,
+}
+
+/// The widgets of one zone and the settings of its `zone` marker. Widgets
+/// before the first marker belong to the left zone.
+pub(super) struct Zone<'c> {
+    align: Align,
+    separator: Option<&'c str>,
+    overflow: Overflow,
+    pub(super) widgets: Vec<&'c LineWidgetConfig>,
+}
+
+type Rendered<'c> = Vec<(WidgetOutput, &'c LineWidgetConfig)>;
+
+/// Split a line at its `zone` markers into left, center and right zones.
+/// Lines without markers return None and lay out as before.
+pub(super) fn split(line: &[LineWidgetConfig]) -> Option<[Zone<'_>; 3]> {
+    if !line.iter().any(|wc| wc.widget_type == "zone") {
+        return None;
+    }
+
+    let mut zones = [Align::Left, Align::Center, Align::Right].map(|align| Zone {
+        align,
+        separator: None,
+        overflow: Overflow::Drop,
+        widgets: Vec::new(),
+    });
+    let mut current = Align::Left;
+    for wc in line {
+        match wc.widget_type.as_str() {
+            "zone" => {
+                current = match wc.metadata.get("align").map(String::as_str) {
+                    Some("left") => Align::Left,
+                    Some("center") => Align::Center,
+                    _ => Align::Right,
+                };
+                let zone = &mut zones[current as usize];
+                if let Some(separator) = wc.metadata.get("separator") {
+                    zone.separator = Some(separator);
+                }
+                zone.overflow = match wc.metadata.get("overflow").map(String::as_str) {
+                    Some("drop") => Overflow::Drop,
+                    Some("hide") => Overflow::Hide,
+                    Some("truncate") => Overflow::Truncate,
+                    _ => zone.overflow,
+                };
+            }
+            // Zones already push each other apart.
+            "flex-separator" => {}
+            _ => zones[current as usize].widgets.push(wc),
+        }
+    }
+    Some(zones)
+}
+
+impl LayoutEngine<'_> {
+    /// Fit and assemble a zoned line: the center zone is centered on the
+    /// line, the right zone is flush right, and each is at least a column
+    /// from its neighbours. None when every zone ended up empty.
+    pub(super) fn layout_zones(
+        &self,
+        zones: &[Zone; 3],
+        mut rendered: [Rendered; 3],
+        max_width: usize,
+    ) -> Option<String> {
+        self.fit_zones(zones, &mut rendered, max_width);
+        if rendered.iter().all(Vec::is_empty) {
+            return None;
+        }
+
+        let [left, center, right] = [0, 1, 2].map(|z| {
+            if rendered[z].is_empty() {
+                (String::new(), 0)
+            } else {
+                (
+                    self.assemble_zone(&zones[z], &rendered[z]),
+                    self.zone_width(&zones[z], &rendered[z]),
+                )
+            }
+        });
+
+        let mut line = left.0;
+        let mut col = left.1;
+        let gap = |col: usize| if col > 0 { col + 1 } else { 0 };
+        if center.1 > 0 {
+            let limit = match right.1 {
+                0 => max_width,
+                width => max_width.saturating_sub(width + 1),
+            };
+            let start = (max_width.saturating_sub(center.1) / 2)
+                .min(limit.saturating_sub(center.1))
+                .max(gap(col));
+            line.push_str(&" ".repeat(start - col));
+            line.push_str(&center.0);
+            col = start + center.1;
+        }
+        if right.1 > 0 {
+            let start = max_width.saturating_sub(right.1).max(gap(col));
+            line.push_str(&" ".repeat(start - col));
+            line.push_str(&right.0);
+        }
+
+        Some(format!("{line}{}", self.renderer.reset()))
+    }
+
+    /// Display width of a zoned line, including the gaps between zones.
+    pub(super) fn zones_width(&self, zones: &[Zone; 3], rendered: &[Rendered; 3]) -> usize {
+        let widths: Vec<usize> = (0..3)
+            .map(|z| self.zone_width(&zones[z], &rendered[z]))
+            .filter(|&w| w > 0)
+            .collect();
+        widths.iter().sum::<usize>() + widths.len().saturating_sub(1)
+    }
+
+    fn zone_width(&self, zone: &Zone, widgets: &Rendered) -> usize {
+        if widgets.is_empty() {
+            return 0;
+        }
+        let config = self.config;
+        let sep_width = UnicodeWidthStr::width(self.zone_separator(zone));
+
+        let mut total = 0;
+        for (i, (output, wc)) in widgets.iter().enumerate() {
+            if i > 0 && !widgets[i - 1].1.merge_next {
+                total += sep_width;
+            }
+            let padding = wc.padding.as_deref().unwrap_or(&config.default_padding);
+            total += output.display_width + UnicodeWidthStr::width(padding) * 2;
+        }
+
+        if config.powerline.enabled {
+            let powerline = &config.powerline;
+            let cap_width = |cap: &Option<String>| cap.as_deref().map_or(0, UnicodeWidthStr::width);
+            let edge_width = UnicodeWidthStr::width(powerline.separator.as_str());
+            // Left zones close with a separator, right zones open with a
+            // reverse one, and the center does both.
+            total += match zone.align {
+                Align::Left => cap_width(&powerline.start_cap) + edge_width,
+                Align::Center => 1 + edge_width,
+                Align::Right => 1 + cap_width(&powerline.end_cap),
+            };
+        }
+        total
+    }
+
+    fn zone_separator<'s>(&'s self, zone: &Zone<'s>) -> &'s str {
+        zone.separator.unwrap_or(if self.config.powerline.enabled {
+            &self.config.powerline.separator
+        } else {
+            &self.config.default_separator
+        })
+    }
+
+    /// Shrink zones until the line fits, lowest priority first, each as
+    /// its overflow policy says. The center gives way only once the side
+    /// zones have nothing left to give.
+    fn fit_zones(&self, zones: &[Zone; 3], rendered: &mut [Rendered; 3], max_width: usize) {
+        loop {
+            let width = self.zones_width(zones, rendered);
+            if width <= max_width {
+                return;
+            }
+
+            let next =
+                next_shrink(zones, rendered, false).or_else(|| next_shrink(zones, rendered, true));
+            let Some((z, shrink)) = next else {
+                return;
+            };
+            match shrink {
+                Shrink::Drop(group) => {
+                    let at = group.start;
+                    rendered[z].drain(group);
+                    prune_separators(&mut rendered[z], at);
+                }
+                Shrink::Hide => rendered[z].clear(),
+                Shrink::Truncate => truncate_zone(&mut rendered[z], width - max_width),
+            }
+        }
+    }
+
+    fn assemble_zone(&self, zone: &Zone, widgets: &Rendered) -> String {
+        let config = self.config;
+        let separator = self.zone_separator(zone);
+        let mut parts: Vec<String> = Vec::new();
+
+        if !config.powerline.enabled {
+            for (i, (output, wc)) in widgets.iter().enumerate() {
+                if i > 0 && !widgets[i - 1].1.merge_next {
+                    parts.push(separator.to_string());
+                }
+                let padding = wc.padding.as_deref().unwrap_or(&config.default_padding);
+                let styled = self.apply_style(&output.text, wc, output);
+                parts.push(format!("{padding}{styled}{padding}"));
+            }
+            return parts.join("");
+        }
+
+        let edge = |glyph: &str, wc: &LineWidgetConfig| {
+            format!(
+                "{}{}{}",
+                self.renderer
+                    .fg(&Renderer::parse_color(self.powerline_bg(wc))),
+                glyph,
+                self.renderer.reset(),
+            )
+        };
+        let (first, last) = (widgets[0].1, widgets[widgets.len() - 1].1);
+
+        match (zone.align, &config.powerline.start_cap) {
+            (Align::Left, Some(cap)) => parts.push(edge(cap, first)),
+            (Align::Left, None) => {}
+            // reverse powerline separator
+            _ => parts.push(edge("\u{E0B2}", first)),
+        }
+        let refs: Vec<_> = widgets.iter().collect();
+        let mut width = 0;
+        self.render_powerline_segment(&refs, &mut parts, &mut width, usize::MAX, separator);
+        match (zone.align, &config.powerline.end_cap) {
+            (Align::Right, Some(cap)) => parts.push(edge(cap, last)),
+            (Align::Right, None) => {}
+            _ => parts.push(edge(&config.powerline.separator, last)),
+        }
+        parts.join("")
+    }
+}
+
+/// One step of shrinking a zone.
+enum Shrink {
+    Drop(Range<usize>),
+    Hide,
+    Truncate,
+}
+
+/// The lowest-priority way to shrink the side zones, or with `center` the
+/// center zone. On equal priority the rightmost widget goes first.
+fn next_shrink(
+    zones: &[Zone; 3],
+    rendered: &[Rendered; 3],
+    center: bool,
+) -> Option<(usize, Shrink)> {
+    let mut candidates = Vec::new();
+    for (z, widgets) in rendered.iter().enumerate() {
+        if (zones[z].align == Align::Center) != center || widgets.is_empty() {
+            continue;
+        }
+        match zones[z].overflow {
+            Overflow::Drop => {
+                for group in merge_groups(widgets) {
+                    if group.clone().all(|i| is_structural(widgets[i].1)) {
+                        continue;
+                    }
+                    let priority = group.clone().map(|i| widgets[i].0.priority).max();
+                    let key = (priority, Reverse(z), Reverse(group.start));
+                    candidates.push((key, z, Shrink::Drop(group)));
+                }
+            }
+            Overflow::Hide => {
+                let priority = widgets.iter().map(|(output, _)| output.priority).max();
+                candidates.push(((priority, Reverse(z), Reverse(0)), z, Shrink::Hide));
+            }
+            Overflow::Truncate => {
+                let last = widgets.len() - 1;
+                let key = (Some(widgets[last].0.priority), Reverse(z), Reverse(last));
+                candidates.push((key, z, Shrink::Truncate));
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .min_by_key(|(key, _, _)| *key)
+        .map(|(_, z, shrink)| (z, shrink))
+}
+
+/// Cut `over` columns from the end of a zone: shorten its last widget and
+/// append an ellipsis, or drop the widget when too little of it would be left.
+fn truncate_zone(widgets: &mut Rendered, over: usize) {
+    let Some((output, _)) = widgets.last_mut() else {
+        return;
+    };
+    if output.display_width > over + 1 {
+        let keep = output.display_width - over - 1;
+        let mut text = String::new();
+        let mut width = 0;
+        for c in strip_ansi(&output.text).chars() {
+            width += c.width().unwrap_or(0);
+            if width > keep {
+                break;
+            }
+            text.push(c);
+        }
+        output.text = format!("{text}…");
+        output.display_width = display_width(&output.text);
+    } else {
+        widgets.pop();
+        let at = widgets.len();
+        prune_separators(widgets, at);
+    }
+}
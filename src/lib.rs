@@ -1,13 +1,18 @@
+pub mod budget;
+pub mod calendar;
 pub mod config;
+pub mod git;
 pub mod layout;
 pub mod license;
+pub mod pricing;
 pub mod render;
 pub mod storage;
 pub mod themes;
+pub mod transcript;
 pub mod tui;
 pub mod widgets;
 
 pub use config::Config;
 pub use render::Renderer;
 pub use storage::CostTracker;
-pub use widgets::{Widget, WidgetConfig, WidgetOutput, WidgetRegistry};
+pub use widgets::{RenderContext, Widget, WidgetConfig, WidgetOutput, WidgetRegistry};
@@ -1,17 +1,20 @@
+#[cfg(feature = "online-license")]
+mod online;
 mod storage;
 mod verify;
 
 pub use storage::LicenseStorage;
 pub use verify::{LicenseInfo, LicenseStatus, LicenseTier, LicenseValidator};
 
-/// Check whether Pro features are currently available.
+/// Check whether Pro features are currently available, including during the
+/// offline grace period.
 /// Returns the license info if valid, None otherwise.
 pub fn check_pro() -> Option<LicenseInfo> {
     let storage = LicenseStorage::new();
     let key = storage.load_key()?;
     let validator = LicenseValidator::new();
     let info = validator.validate(&key);
-    if info.status == LicenseStatus::Valid {
+    if matches!(info.status, LicenseStatus::Valid | LicenseStatus::GracePeriod) {
         Some(info)
     } else {
         None
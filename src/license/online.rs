@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::verify::LicenseTier;
+
+/// Production license server (see `server/src/worker.js`).
+pub const DEFAULT_BASE_URL: &str = "https://api.claude-status.dev";
+
+/// Environment variable that overrides the license server base URL.
+pub const BASE_URL_ENV: &str = "CLAUDE_STATUS_LICENSE_URL";
+
+/// Keep this short: validation can run from the status line render path.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Why a license server call produced no verdict.
+#[derive(Debug)]
+pub enum OnlineError {
+    /// Timed out, connection refused, server error or unreadable reply.
+    /// Callers fall back to the cached or offline result.
+    Unreachable(String),
+    /// The server answered and turned the key down, with its reason code.
+    Rejected(String),
+}
+
+impl std::fmt::Display for OnlineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreachable(e) => write!(f, "license server unreachable: {e}"),
+            Self::Rejected(reason) => write!(f, "license server rejected the key: {reason}"),
+        }
+    }
+}
+
+/// Reply body shared by the verify, activate and deactivate endpoints.
+/// Verify reports `valid`, the other two report `success`.
+#[derive(Debug, Deserialize)]
+pub struct ServerReply {
+    pub valid: Option<bool>,
+    pub success: Option<bool>,
+    pub tier: Option<String>,
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub reason: Option<String>,
+    pub max_devices: Option<u32>,
+}
+
+impl ServerReply {
+    pub fn tier(&self) -> LicenseTier {
+        match self.tier.as_deref() {
+            Some("lifetime") => LicenseTier::Lifetime,
+            _ => LicenseTier::Pro,
+        }
+    }
+
+    /// The server's explicit yes or no, if the reply carries one.
+    fn verdict(&self) -> Option<bool> {
+        self.valid.or(self.success)
+    }
+
+    fn rejection(&self) -> String {
+        match (self.reason.as_deref(), self.max_devices) {
+            (Some("device_limit"), Some(max)) => format!("device_limit (max {max} devices)"),
+            (Some(reason), _) => reason.to_string(),
+            (None, _) => "unknown".to_string(),
+        }
+    }
+}
+
+pub struct LicenseClient {
+    base_url: String,
+}
+
+impl LicenseClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The base URL from `CLAUDE_STATUS_LICENSE_URL`, or the production server.
+    pub fn default_base_url() -> String {
+        std::env::var(BASE_URL_ENV)
+            .ok()
+            .filter(|u| !u.is_empty())
+            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+    }
+
+    pub fn verify(&self, key: &str, machine_id: &str) -> Result<ServerReply, OnlineError> {
+        self.post("/v1/license/verify", key, machine_id)
+    }
+
+    pub fn activate(&self, key: &str, machine_id: &str) -> Result<ServerReply, OnlineError> {
+        self.post("/v1/license/activate", key, machine_id)
+    }
+
+    pub fn deactivate(&self, key: &str, machine_id: &str) -> Result<ServerReply, OnlineError> {
+        self.post("/v1/license/deactivate", key, machine_id)
+    }
+
+    fn post(&self, path: &str, key: &str, machine_id: &str) -> Result<ServerReply, OnlineError> {
+        let url = format!("{}{}", self.base_url, path);
+        let body = json!({ "key": key, "machine_id": machine_id });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| OnlineError::Unreachable(e.to_string()))?;
+
+        let reply = runtime.block_on(async {
+            let client = reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .map_err(|e| OnlineError::Unreachable(e.to_string()))?;
+            let response = client
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| OnlineError::Unreachable(e.to_string()))?;
+            let status = response.status();
+            if status.is_server_error() {
+                return Err(OnlineError::Unreachable(format!(
+                    "server returned {status}"
+                )));
+            }
+            response
+                .json::<ServerReply>()
+                .await
+                .map(|reply| (status, reply))
+                .map_err(|e| OnlineError::Unreachable(e.to_string()))
+        })?;
+
+        // Only the license server's own verdicts count: a rate limit, a
+        // wrong base URL or a proxy's error page must not revoke a key
+        let (status, reply) = reply;
+        match reply.verdict() {
+            Some(true) if status.is_success() => Ok(reply),
+            // The server answers 400 to requests it can't accept
+            Some(false) if status.is_success() || status == reqwest::StatusCode::BAD_REQUEST => {
+                Err(OnlineError::Rejected(reply.rejection()))
+            }
+            _ => Err(OnlineError::Unreachable(format!(
+                "unexpected reply from server ({status})"
+            ))),
+        }
+    }
+}
+
+/// Serve one canned JSON reply on a local port and return its base URL.
+#[cfg(test)]
+pub fn stand_in_server(status: u16, body: &'static str) -> String {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        if let Ok((mut stream, _)) = listener.accept() {
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let response = format!(
+                "HTTP/1.1 {status} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_accepted() {
+        let url = stand_in_server(
+            200,
+            r#"{"valid":true,"tier":"lifetime","expires":null,"features":["cost_tracking"]}"#,
+        );
+        let reply = LicenseClient::new(&url)
+            .verify("CS-PRO-AAAA-BBBB-CCCC-DDDD", "m1")
+            .unwrap();
+        assert_eq!(reply.tier(), LicenseTier::Lifetime);
+        assert_eq!(reply.features, vec!["cost_tracking".to_string()]);
+    }
+
+    #[test]
+    fn test_activate_device_limit_is_rejected() {
+        let url = stand_in_server(
+            200,
+            r#"{"success":false,"reason":"device_limit","max_devices":3}"#,
+        );
+        match LicenseClient::new(&url).activate("CS-PRO-AAAA-BBBB-CCCC-DDDD", "m1") {
+            Err(OnlineError::Rejected(reason)) => {
+                assert_eq!(reason, "device_limit (max 3 devices)")
+            }
+            other => panic!("expected rejection, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_bad_request_is_rejected() {
+        let url = stand_in_server(400, r#"{"success":false,"reason":"invalid_format"}"#);
+        assert!(matches!(
+            LicenseClient::new(&url).activate("CS-PRO-AAAA-BBBB-CCCC-DDDD", "m1"),
+            Err(OnlineError::Rejected(reason)) if reason == "invalid_format"
+        ));
+    }
+
+    #[test]
+    fn test_reply_without_verdict_is_unreachable() {
+        for (status, body) in [
+            (429, r#"{"error":"rate limited"}"#),
+            (404, r#"{"valid":false,"error":"not found"}"#),
+            (401, r#"{"message":"unauthorized"}"#),
+            (200, r#"{"status":"ok"}"#),
+        ] {
+            let url = stand_in_server(status, body);
+            assert!(
+                matches!(
+                    LicenseClient::new(&url).verify("CS-PRO-AAAA-BBBB-CCCC-DDDD", "m1"),
+                    Err(OnlineError::Unreachable(_))
+                ),
+                "{status} {body}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_server_error_is_unreachable() {
+        let url = stand_in_server(503, r#"{"error":"unavailable"}"#);
+        assert!(matches!(
+            LicenseClient::new(&url).verify("CS-PRO-AAAA-BBBB-CCCC-DDDD", "m1"),
+            Err(OnlineError::Unreachable(_))
+        ));
+    }
+}
@@ -158,6 +158,7 @@ mod tests {
             expires: None,
             features: vec!["cost_tracking".to_string()],
             validated_at: Utc::now(),
+            checked_at: None,
         };
         storage.save_cache(&cache).unwrap();
 
@@ -181,6 +182,7 @@ mod tests {
             expires: None,
             features: vec![],
             validated_at: Utc::now(),
+            checked_at: None,
         };
         storage.save_cache(&cache).unwrap();
         assert!(storage.load_cache().is_some());
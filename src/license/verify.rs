@@ -2,6 +2,8 @@ use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+#[cfg(feature = "online-license")]
+use super::online::{LicenseClient, OnlineError};
 use super::storage::LicenseStorage;
 
 /// License key format: CS-PRO-XXXX-XXXX-XXXX-XXXX (hex chars)
@@ -15,6 +17,10 @@ const OFFLINE_GRACE_DAYS: i64 = 7;
 /// How often to re-validate with the server (hours)
 const REVALIDATION_HOURS: i64 = 24;
 
+/// Minimum gap between attempts to reach an unresponsive server (minutes)
+#[cfg(feature = "online-license")]
+const RETRY_MINUTES: i64 = 60;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LicenseTier {
     Free,
@@ -49,19 +55,55 @@ pub struct ValidationCache {
     pub expires: Option<DateTime<Utc>>,
     pub features: Vec<String>,
     pub validated_at: DateTime<Utc>,
+    /// Last time the license server was contacted, whether or not it answered.
+    #[serde(default)]
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+impl ValidationCache {
+    /// Placeholder for a key the server has never answered for, recording
+    /// only when it was last tried. It never counts as a validation.
+    #[cfg(feature = "online-license")]
+    fn pending(checked_at: DateTime<Utc>) -> Self {
+        Self {
+            valid: false,
+            tier: LicenseTier::Free,
+            expires: None,
+            features: vec![],
+            validated_at: DateTime::UNIX_EPOCH,
+            checked_at: Some(checked_at),
+        }
+    }
 }
 
 pub struct LicenseValidator {
     storage: LicenseStorage,
+    #[cfg(feature = "online-license")]
+    client: LicenseClient,
 }
 
 impl LicenseValidator {
     pub fn new() -> Self {
         Self {
             storage: LicenseStorage::new(),
+            #[cfg(feature = "online-license")]
+            client: LicenseClient::new(&LicenseClient::default_base_url()),
         }
     }
 
+    /// Talk to a different license server, e.g. a local stand-in.
+    #[cfg(feature = "online-license")]
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.client = LicenseClient::new(base_url);
+        self
+    }
+
+    #[cfg(all(test, feature = "online-license"))]
+    fn with_storage(mut self, storage: LicenseStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
     /// Validate a license key. Uses cached validation if recent enough,
     /// otherwise attempts online validation with graceful fallback.
     pub fn validate(&self, key: &str) -> LicenseInfo {
@@ -81,21 +123,47 @@ impl LicenseValidator {
         }
 
         // Check cached validation
-        if let Some(cache) = self.storage.load_cache() {
+        let cache = self.storage.load_cache();
+        if let Some(cache) = &cache {
             let age = Utc::now() - cache.validated_at;
 
-            if cache.valid && age < Duration::hours(REVALIDATION_HOURS) {
-                // Cache is fresh and valid
+            if age < Duration::hours(REVALIDATION_HOURS) {
+                // Cache is fresh: either valid, or the server recently rejected the key
                 return LicenseInfo {
-                    tier: cache.tier,
-                    status: LicenseStatus::Valid,
+                    tier: cache.tier.clone(),
+                    status: if cache.valid {
+                        LicenseStatus::Valid
+                    } else {
+                        LicenseStatus::Invalid
+                    },
                     key: key.to_string(),
                     expires: cache.expires,
-                    features: cache.features,
+                    features: cache.features.clone(),
                     last_validated: Some(cache.validated_at),
                     machine_id,
                 };
             }
+        }
+
+        #[cfg(feature = "online-license")]
+        if let Some(info) = self.online_validate(key, &machine_id, cache.as_ref()) {
+            return info;
+        }
+
+        self.cached_or_offline(key, &machine_id, cache)
+    }
+
+    /// Result when the server cannot be asked: a stale cache within the
+    /// grace period, otherwise the offline checksum.
+    fn cached_or_offline(
+        &self,
+        key: &str,
+        machine_id: &str,
+        cache: Option<ValidationCache>,
+    ) -> LicenseInfo {
+        if let Some(cache) = cache {
+            let age = Utc::now() - cache.validated_at;
+            let machine_id = machine_id.to_string();
 
             // Cache exists but stale - check grace period
             if cache.valid && age < Duration::days(OFFLINE_GRACE_DAYS) {
@@ -118,7 +186,89 @@ impl LicenseValidator {
         }
 
         // No cache at all - do offline validation
-        self.offline_validate(key, &machine_id)
+        self.offline_validate(key, machine_id)
+    }
+
+    /// Ask the license server about a key whose cache is missing or stale.
+    /// Returns None when the server could not be reached (or was tried too
+    /// recently), leaving the decision to the grace-period logic.
+    #[cfg(feature = "online-license")]
+    fn online_validate(
+        &self,
+        key: &str,
+        machine_id: &str,
+        cache: Option<&ValidationCache>,
+    ) -> Option<LicenseInfo> {
+        let now = Utc::now();
+        if let Some(checked_at) = cache.and_then(|c| c.checked_at)
+            && now - checked_at < Duration::minutes(RETRY_MINUTES)
+        {
+            return None;
+        }
+
+        match self.client.verify(key, machine_id) {
+            Ok(reply) => {
+                let cache = ValidationCache {
+                    valid: true,
+                    tier: reply.tier(),
+                    expires: reply.expires,
+                    features: if reply.features.is_empty() {
+                        pro_features()
+                    } else {
+                        reply.features
+                    },
+                    validated_at: now,
+                    checked_at: Some(now),
+                };
+                let _ = self.storage.save_cache(&cache);
+                Some(LicenseInfo {
+                    tier: cache.tier,
+                    status: LicenseStatus::Valid,
+                    key: key.to_string(),
+                    expires: cache.expires,
+                    features: cache.features,
+                    last_validated: Some(now),
+                    machine_id: machine_id.to_string(),
+                })
+            }
+            Err(OnlineError::Rejected(reason)) => {
+                let cache = ValidationCache {
+                    valid: false,
+                    tier: LicenseTier::Free,
+                    expires: None,
+                    features: vec![],
+                    validated_at: now,
+                    checked_at: Some(now),
+                };
+                let _ = self.storage.save_cache(&cache);
+                Some(LicenseInfo {
+                    tier: LicenseTier::Free,
+                    status: if reason == "expired" {
+                        LicenseStatus::Expired
+                    } else {
+                        LicenseStatus::Invalid
+                    },
+                    key: key.to_string(),
+                    expires: None,
+                    features: vec![],
+                    last_validated: Some(now),
+                    machine_id: machine_id.to_string(),
+                })
+            }
+            Err(OnlineError::Unreachable(_)) => {
+                // Record the attempt even without a cache, or every render
+                // would wait on the server again
+                let cache = match cache {
+                    Some(cache) => ValidationCache {
+                        checked_at: Some(now),
+                        ..cache.clone()
+                    },
+                    None => ValidationCache::pending(now),
+                };
+                let _ = self.storage.save_cache(&cache);
+                None
+            }
+        }
     }
 
     /// Activate a license key: validate format and store it.
@@ -131,34 +281,64 @@ impl LicenseValidator {
 
         let machine_id = self.machine_id();
 
-        // Store the key
-        self.storage
-            .save_key(key)
-            .map_err(|e| format!("Failed to save license key: {e}"))?;
-
         // Create initial cache (valid for offline use)
-        let cache = ValidationCache {
+        let offline_cache = || ValidationCache {
             valid: true,
             tier: LicenseTier::Pro,
             expires: None,
             features: pro_features(),
             validated_at: Utc::now(),
+            checked_at: None,
         };
+
+        // Register this machine with the server; fall back to offline
+        // activation when it cannot be reached
+        #[cfg(feature = "online-license")]
+        let cache = match self.client.activate(key.trim(), &machine_id) {
+            Ok(reply) => ValidationCache {
+                valid: true,
+                tier: reply.tier(),
+                expires: reply.expires,
+                features: if reply.features.is_empty() {
+                    pro_features()
+                } else {
+                    reply.features
+                },
+                validated_at: Utc::now(),
+                checked_at: Some(Utc::now()),
+            },
+            Err(e @ OnlineError::Rejected(_)) => return Err(format!("Activation failed: {e}")),
+            Err(OnlineError::Unreachable(_)) => offline_cache(),
+        };
+        #[cfg(not(feature = "online-license"))]
+        let cache = offline_cache();
+
+        // Store the key
+        self.storage
+            .save_key(key)
+            .map_err(|e| format!("Failed to save license key: {e}"))?;
         let _ = self.storage.save_cache(&cache);
 
         Ok(LicenseInfo {
-            tier: LicenseTier::Pro,
+            tier: cache.tier,
             status: LicenseStatus::Valid,
             key: key.to_string(),
-            expires: None,
-            features: pro_features(),
-            last_validated: Some(Utc::now()),
+            expires: cache.expires,
+            features: cache.features,
+            last_validated: Some(cache.validated_at),
             machine_id,
         })
     }
 
     /// Deactivate (remove) the current license.
     pub fn deactivate(&self) -> Result<(), String> {
+        // Free this machine's slot on the server. Best effort: the local
+        // license is removed even when the server cannot be reached.
+        #[cfg(feature = "online-license")]
+        if let Some(key) = self.storage.load_key() {
+            let _ = self.client.deactivate(&key, &self.machine_id());
+        }
+
         self.storage
             .remove_key()
             .map_err(|e| format!("Failed to remove license: {e}"))?;
@@ -349,6 +529,8 @@ fn pro_features() -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "online-license")]
+    use crate::license::online::stand_in_server;
 
     #[test]
     fn test_validate_format_valid() {
@@ -444,4 +626,119 @@ mod tests {
         assert_eq!(deserialized.tier, LicenseTier::Pro);
         assert_eq!(deserialized.status, LicenseStatus::Valid);
     }
+
+    #[cfg(feature = "online-license")]
+    fn online_validator(name: &str, base_url: &str) -> (LicenseValidator, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "claude-status-test-online-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let validator = LicenseValidator::new()
+            .with_base_url(base_url)
+            .with_storage(LicenseStorage::with_dir(dir.clone()));
+        (validator, dir)
+    }
+
+    #[cfg(feature = "online-license")]
+    fn unreachable_url() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    #[cfg(feature = "online-license")]
+    #[test]
+    fn test_validate_online_caches_server_result() {
+        let url = stand_in_server(
+            200,
+            r#"{"valid":true,"tier":"lifetime","expires":null,"features":["cost_tracking"]}"#,
+        );
+        let (validator, dir) = online_validator("verify", &url);
+
+        let info = validator.validate(&generate_key());
+        assert_eq!(info.status, LicenseStatus::Valid);
+        assert_eq!(info.tier, LicenseTier::Lifetime);
+
+        let cache = validator.storage.load_cache().unwrap();
+        assert!(cache.valid);
+        assert!(cache.checked_at.is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "online-license")]
+    #[test]
+    fn test_validate_online_rejection_sticks_until_revalidation() {
+        let url = stand_in_server(200, r#"{"valid":false,"reason":"revoked"}"#);
+        let (validator, dir) = online_validator("revoked", &url);
+        let key = generate_key();
+
+        assert_eq!(validator.validate(&key).status, LicenseStatus::Invalid);
+        // Server is gone now; the fresh rejection is still honoured
+        assert_eq!(validator.validate(&key).status, LicenseStatus::Invalid);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "online-license")]
+    #[test]
+    fn test_validate_unreachable_server_uses_grace_period() {
+        let (validator, dir) = online_validator("grace", &unreachable_url());
+        let stale = ValidationCache {
+            valid: true,
+            tier: LicenseTier::Pro,
+            expires: None,
+            features: pro_features(),
+            validated_at: Utc::now() - Duration::days(2),
+            checked_at: None,
+        };
+        validator.storage.save_cache(&stale).unwrap();
+
+        let info = validator.validate(&generate_key());
+        assert_eq!(info.status, LicenseStatus::GracePeriod);
+        assert!(validator.storage.load_cache().unwrap().checked_at.is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "online-license")]
+    #[test]
+    fn test_validate_unreachable_without_cache_waits_before_retrying() {
+        let (validator, dir) = online_validator("no-cache", &unreachable_url());
+        let key = generate_key();
+
+        let info = validator.validate(&key);
+        assert_eq!(info.status, LicenseStatus::Valid);
+        assert_eq!(info.tier, LicenseTier::Pro);
+        let cache = validator.storage.load_cache().unwrap();
+        assert!(cache.checked_at.is_some());
+
+        // The server is back, but the attempt was too recent to try again
+        let url = stand_in_server(
+            200,
+            r#"{"valid":true,"tier":"lifetime","expires":null,"features":[]}"#,
+        );
+        let validator = validator.with_base_url(&url);
+        let info = validator.validate(&key);
+        assert_eq!(info.status, LicenseStatus::Valid);
+        assert_eq!(info.tier, LicenseTier::Pro);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "online-license")]
+    #[test]
+    fn test_activate_rejected_by_server_stores_nothing() {
+        let url = stand_in_server(
+            200,
+            r#"{"success":false,"reason":"device_limit","max_devices":3}"#,
+        );
+        let (validator, dir) = online_validator("activate", &url);
+
+        let err = validator.activate(&generate_key()).unwrap_err();
+        assert!(err.contains("device_limit"), "got: {err}");
+        assert!(validator.storage.load_key().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
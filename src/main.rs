@@ -1,14 +1,16 @@
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 use std::process;
 
 use clap::Parser;
 
 mod cli;
 
-use claude_status::config::Config;
+use claude_status::config::{Config, ConfigError};
 use claude_status::layout::LayoutEngine;
 use claude_status::render::Renderer;
-use claude_status::widgets::{SessionData, WidgetRegistry};
+use claude_status::storage::{CostTracker, SessionRecord};
+use claude_status::themes::UserTheme;
+use claude_status::widgets::{PluginWidget, SessionData, WidgetRegistry};
 
 #[derive(Parser)]
 #[command(
@@ -33,7 +35,7 @@ fn main() {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(cmd) => cli::handle_command(cmd),
+        Some(cmd) => cli::handle_command(cmd, cli.config.as_deref()),
         None => render_statusline(&cli),
     }
 }
@@ -44,18 +46,79 @@ fn render_statusline(cli: &Cli) {
         process::exit(1);
     }
 
-    let data: SessionData = match serde_json::from_str(&input) {
+    let mut data: SessionData = match serde_json::from_str(&input) {
         Ok(d) => d,
         Err(_) => process::exit(1),
     };
+    data.raw = Some(input);
 
-    let config = Config::load(cli.config.as_deref());
-    let renderer = Renderer::detect(&cli.color_level);
-    let registry = WidgetRegistry::new();
+    let mut renderer = Renderer::detect(&cli.color_level);
+    let mut registry = WidgetRegistry::builtin();
+    if let Some(dir) = PluginWidget::dir() {
+        registry.register_plugins(&dir);
+    }
+    let user_themes = UserTheme::installed();
+    let (config, config_errors) =
+        match Config::load_checked(cli.config.as_deref(), &registry, &user_themes) {
+            Ok(config) => (config, None),
+            Err(errors) => (Config::load(cli.config.as_deref()), Some(errors)),
+        };
+    if config.theme == "auto" {
+        renderer.background = Renderer::detect_background();
+    }
     let engine = LayoutEngine::new(&config, &renderer);
 
-    let lines = engine.render(&data, &config, &registry);
+    let lines = engine.render(&data, &registry);
+    let mut stdout = io::stdout().lock();
+    if let Some(errors) = config_errors {
+        let _ = writeln!(stdout, "{}", config_error_segment(&errors, &renderer));
+    }
     for line in &lines {
-        println!("{line}");
+        let _ = writeln!(stdout, "{line}");
+    }
+    let _ = stdout.flush();
+    drop(stdout);
+
+    record_history(&data);
+}
+
+/// One line summarizing config problems, so a typo shows up in the status
+/// line instead of silently falling back to defaults.
+fn config_error_segment(errors: &[ConfigError], renderer: &Renderer) -> String {
+    let first = errors[0].to_string();
+    let mut text = format!(
+        "\u{26A0} config {}",
+        first.lines().next().unwrap_or_default()
+    );
+    if errors.len() > 1 {
+        text.push_str(&format!(
+            " (+{} more, run `claude-status validate`)",
+            errors.len() - 1
+        ));
+    }
+    format!(
+        "{}{}{}",
+        renderer.fg(&Renderer::parse_color("red")),
+        text,
+        renderer.reset()
+    )
+}
+
+/// Persist this render's session totals so history-based widgets and `stats`
+/// have data. Failures are ignored: the status line must never break on I/O.
+fn record_history(data: &SessionData) {
+    let now = chrono::Utc::now().timestamp();
+    let Some(mut record) = SessionRecord::from_session_data(data, now) else {
+        return;
+    };
+    if let Ok(mut tracker) = CostTracker::open() {
+        // The remote is looked up once, on the session's first render
+        if tracker.get_session(&record.id).is_none() {
+            record.git_remote = record
+                .project
+                .as_deref()
+                .and_then(claude_status::git::remote_url);
+        }
+        let _ = tracker.record_session(&record);
     }
 }
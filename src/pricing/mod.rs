@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::widgets::{CurrentUsage, SessionData};
+
+/// Token prices for one model, in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "RateTable")]
+pub struct ModelRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+/// `[pricing.<model>]` as written in the config; the cache rates are derived
+/// from the input rate when left out.
+#[derive(Deserialize)]
+struct RateTable {
+    input: f64,
+    output: f64,
+    cache_write: Option<f64>,
+    cache_read: Option<f64>,
+}
+
+impl From<RateTable> for ModelRates {
+    fn from(t: RateTable) -> Self {
+        let derived = Self::new(t.input, t.output);
+        Self {
+            cache_write: t.cache_write.unwrap_or(derived.cache_write),
+            cache_read: t.cache_read.unwrap_or(derived.cache_read),
+            ..derived
+        }
+    }
+}
+
+impl ModelRates {
+    const fn new(input: f64, output: f64) -> Self {
+        // Cache writes cost 1.25x input, cache reads 0.1x input.
+        Self {
+            input,
+            output,
+            cache_write: input * 1.25,
+            cache_read: input * 0.1,
+        }
+    }
+
+    /// Cost in USD of a single request's token usage.
+    pub fn cost(&self, usage: &CurrentUsage) -> f64 {
+        (usage.input_tokens.unwrap_or(0) as f64 * self.input
+            + usage.output_tokens.unwrap_or(0) as f64 * self.output
+            + usage.cache_creation_input_tokens.unwrap_or(0) as f64 * self.cache_write
+            + usage.cache_read_input_tokens.unwrap_or(0) as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in rates keyed by a fragment of the model id. The longest fragment
+/// contained in the id wins, so `opus-4-6` takes precedence over `opus`.
+/// Claude 3 ids put the version first, as in `claude-3-5-haiku-20241022`.
+const BUILTIN_RATES: &[(&str, ModelRates)] = &[
+    ("opus", ModelRates::new(15.0, 75.0)),
+    ("opus-4-5", ModelRates::new(5.0, 25.0)),
+    ("opus-4-6", ModelRates::new(5.0, 25.0)),
+    ("3-opus", ModelRates::new(15.0, 75.0)),
+    ("sonnet", ModelRates::new(3.0, 15.0)),
+    ("haiku", ModelRates::new(1.0, 5.0)),
+    ("3-haiku", ModelRates::new(0.25, 1.25)),
+    ("3-5-haiku", ModelRates::new(0.8, 4.0)),
+];
+
+/// Per-model pricing: the built-in table with the `[pricing]` overrides from
+/// the config layered on top. Cheap to clone.
+#[derive(Debug, Clone, Default)]
+pub struct Pricing {
+    overrides: Arc<HashMap<String, ModelRates>>,
+}
+
+impl Pricing {
+    pub fn new(overrides: &HashMap<String, ModelRates>) -> Self {
+        Self {
+            overrides: Arc::new(
+                overrides
+                    .iter()
+                    .map(|(k, v)| (k.to_lowercase(), *v))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Rates for a model id or name such as `claude-opus-4-6` or `Sonnet`.
+    /// Overrides are matched the same way as the built-in table and take
+    /// precedence over it.
+    pub fn rates(&self, model: &str) -> Option<ModelRates> {
+        let model = model.to_lowercase();
+        let longest = |entries: &mut dyn Iterator<Item = (&str, ModelRates)>| {
+            entries
+                .filter(|(key, _)| model.contains(key))
+                .max_by_key(|(key, _)| key.len())
+                .map(|(_, rates)| rates)
+        };
+        longest(&mut self.overrides.iter().map(|(k, v)| (k.as_str(), *v)))
+            .or_else(|| longest(&mut BUILTIN_RATES.iter().copied()))
+    }
+
+    /// Rates for the model in the session input, by id or display name.
+    pub fn session_rates(&self, data: &SessionData) -> Option<ModelRates> {
+        let model = data.model.as_ref()?;
+        model
+            .id
+            .as_deref()
+            .and_then(|id| self.rates(id))
+            .or_else(|| model.display_name.as_deref().and_then(|n| self.rates(n)))
+    }
+
+    /// Cost of the most recent request, from `context_window.current_usage`.
+    pub fn turn_cost(&self, data: &SessionData) -> Option<f64> {
+        let usage = data.context_window.as_ref()?.current_usage.as_ref()?;
+        Some(self.session_rates(data)?.cost(usage))
+    }
+
+    /// What the session so far would have cost on `target`. The reported
+    /// session cost is scaled by the price ratio for the current token mix;
+    /// without a reported cost, session token totals are priced directly.
+    pub fn cost_on(&self, data: &SessionData, target: &str) -> Option<f64> {
+        let target = self.rates(target)?;
+        let cw = data.context_window.as_ref();
+
+        let reported = data.cost.as_ref().and_then(|c| c.total_cost_usd);
+        let current = self.session_rates(data);
+        let usage = cw.and_then(|cw| cw.current_usage.as_ref());
+        if let (Some(reported), Some(current), Some(usage)) = (reported, current, usage) {
+            let base = current.cost(usage);
+            if base > 0.0 {
+                return Some(reported * target.cost(usage) / base);
+            }
+        }
+
+        let cw = cw?;
+        let totals = CurrentUsage {
+            input_tokens: cw.total_input_tokens,
+            output_tokens: cw.total_output_tokens,
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: None,
+        };
+        if totals.input_tokens.is_none() && totals.output_tokens.is_none() {
+            return None;
+        }
+        Some(target.cost(&totals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::{ContextWindow, Cost, Model};
+
+    fn session(model: &str, cost: Option<f64>) -> SessionData {
+        SessionData {
+            model: Some(Model {
+                id: Some(model.into()),
+                display_name: None,
+            }),
+            cost: Some(Cost {
+                total_cost_usd: cost,
+                ..Default::default()
+            }),
+            context_window: Some(ContextWindow {
+                total_input_tokens: Some(1_000_000),
+                total_output_tokens: Some(100_000),
+                current_usage: Some(CurrentUsage {
+                    input_tokens: Some(1_000),
+                    output_tokens: Some(2_000),
+                    cache_creation_input_tokens: Some(10_000),
+                    cache_read_input_tokens: Some(100_000),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_longest_builtin_match_wins() {
+        let pricing = Pricing::default();
+        assert_eq!(pricing.rates("claude-opus-4-6").unwrap().input, 5.0);
+        assert_eq!(pricing.rates("claude-opus-4-1").unwrap().input, 15.0);
+        assert_eq!(pricing.rates("Sonnet").unwrap().output, 15.0);
+        assert!(pricing.rates("gpt-4").is_none());
+    }
+
+    #[test]
+    fn test_claude_3_ids_match_version_first_keys() {
+        let pricing = Pricing::default();
+        let rates = |id| pricing.rates(id).unwrap();
+        assert_eq!(
+            rates("claude-3-5-haiku-20241022"),
+            ModelRates::new(0.8, 4.0)
+        );
+        assert_eq!(
+            rates("claude-3-haiku-20240307"),
+            ModelRates::new(0.25, 1.25)
+        );
+        assert_eq!(rates("claude-3-opus-20240229"), ModelRates::new(15.0, 75.0));
+        assert_eq!(rates("claude-3-5-sonnet-20241022").output, 15.0);
+        assert_eq!(
+            rates("claude-haiku-4-5-20251001"),
+            ModelRates::new(1.0, 5.0)
+        );
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "Sonnet".to_string(),
+            ModelRates {
+                input: 1.0,
+                output: 2.0,
+                cache_write: 0.0,
+                cache_read: 0.0,
+            },
+        );
+        let pricing = Pricing::new(&overrides);
+        assert_eq!(pricing.rates("claude-sonnet-4-5").unwrap().output, 2.0);
+        assert_eq!(pricing.rates("claude-haiku-4-5").unwrap().output, 5.0);
+    }
+
+    #[test]
+    fn test_turn_cost_prices_all_token_kinds() {
+        let pricing = Pricing::default();
+        let cost = pricing
+            .turn_cost(&session("claude-sonnet-4-5", None))
+            .unwrap();
+        // 1K in @3 + 2K out @15 + 10K write @3.75 + 100K read @0.3
+        let expected = (3_000.0 + 30_000.0 + 37_500.0 + 30_000.0) / 1_000_000.0;
+        assert!((cost - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_on_scales_reported_cost() {
+        let pricing = Pricing::default();
+        let on_sonnet = pricing
+            .cost_on(&session("claude-opus-4-1", Some(10.0)), "sonnet")
+            .unwrap();
+        assert!((on_sonnet - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cost_on_without_reported_cost_uses_totals() {
+        let pricing = Pricing::default();
+        let on_haiku = pricing
+            .cost_on(&session("claude-opus-4-6", None), "haiku")
+            .unwrap();
+        // 1M in @1 + 100K out @5
+        assert!((on_haiku - 1.5).abs() < 1e-9);
+    }
+}
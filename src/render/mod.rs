@@ -1,4 +1,11 @@
 use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the terminal to answer a background color query.
+const BACKGROUND_QUERY_TIMEOUT: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ColorLevel {
@@ -15,8 +22,18 @@ pub enum ColorSpec {
     Rgb(u8, u8, u8),
 }
 
+/// Whether the terminal draws on a light or a dark background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Light,
+    Dark,
+}
+
 pub struct Renderer {
     pub color_level: ColorLevel,
+    /// The terminal background, when known; picks the variant of
+    /// `theme = "auto"`.
+    pub background: Option<Background>,
 }
 
 impl Renderer {
@@ -28,7 +45,10 @@ impl Renderer {
             "truecolor" => ColorLevel::TrueColor,
             _ => Self::detect_color_level(),
         };
-        Self { color_level }
+        Self {
+            color_level,
+            background: None,
+        }
     }
 
     fn detect_color_level() -> ColorLevel {
@@ -48,6 +68,38 @@ impl Renderer {
         ColorLevel::Basic16
     }
 
+    /// The terminal background for `theme = "auto"`: from `COLORFGBG`, or
+    /// else as last saved by `claude-status theme detect`. The terminal
+    /// itself is never asked while rendering, since it belongs to Claude Code.
+    pub fn detect_background() -> Option<Background> {
+        if let Some(background) = env::var("COLORFGBG").ok().and_then(|v| parse_colorfgbg(&v)) {
+            return Some(background);
+        }
+        match fs::read_to_string(background_path()?).ok()?.trim() {
+            "light" => Some(Background::Light),
+            "dark" => Some(Background::Dark),
+            _ => None,
+        }
+    }
+
+    /// Ask the terminal for its background color and save the answer for
+    /// later renders. Run from the command line only: the terminal is put in
+    /// raw mode for the query.
+    pub fn query_background() -> Result<(Background, PathBuf), String> {
+        let background =
+            query_terminal().ok_or("the terminal did not report its background color")?;
+        let path = background_path().ok_or("no config directory to save it in")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let text = match background {
+            Background::Light => "light",
+            Background::Dark => "dark",
+        };
+        fs::write(&path, text).map_err(|e| e.to_string())?;
+        Ok((background, path))
+    }
+
     pub fn fg(&self, color: &ColorSpec) -> String {
         match self.color_level {
             ColorLevel::None => String::new(),
@@ -90,32 +142,38 @@ impl Renderer {
         }
     }
 
+    /// Parse a color name, `#rrggbb` or ANSI 256 index; unknown values
+    /// fall back to white.
     pub fn parse_color(name: &str) -> ColorSpec {
+        Self::try_parse_color(name).unwrap_or_else(|| ColorSpec::Named("white".into()))
+    }
+
+    /// Like `parse_color`, but None for values it doesn't recognize.
+    pub fn try_parse_color(name: &str) -> Option<ColorSpec> {
         match name {
-            "black" => ColorSpec::Named("black".into()),
-            "red" => ColorSpec::Named("red".into()),
-            "green" => ColorSpec::Named("green".into()),
-            "yellow" => ColorSpec::Named("yellow".into()),
-            "blue" => ColorSpec::Named("blue".into()),
-            "magenta" => ColorSpec::Named("magenta".into()),
-            "cyan" => ColorSpec::Named("cyan".into()),
-            "white" => ColorSpec::Named("white".into()),
-            "brightBlack" | "bright_black" => ColorSpec::Named("brightBlack".into()),
-            "brightRed" | "bright_red" => ColorSpec::Named("brightRed".into()),
-            "brightGreen" | "bright_green" => ColorSpec::Named("brightGreen".into()),
-            "brightYellow" | "bright_yellow" => ColorSpec::Named("brightYellow".into()),
-            "brightBlue" | "bright_blue" => ColorSpec::Named("brightBlue".into()),
-            "brightMagenta" | "bright_magenta" => ColorSpec::Named("brightMagenta".into()),
-            "brightCyan" | "bright_cyan" => ColorSpec::Named("brightCyan".into()),
-            "brightWhite" | "bright_white" => ColorSpec::Named("brightWhite".into()),
+            "black" => Some(ColorSpec::Named("black".into())),
+            "red" => Some(ColorSpec::Named("red".into())),
+            "green" => Some(ColorSpec::Named("green".into())),
+            "yellow" => Some(ColorSpec::Named("yellow".into())),
+            "blue" => Some(ColorSpec::Named("blue".into())),
+            "magenta" => Some(ColorSpec::Named("magenta".into())),
+            "cyan" => Some(ColorSpec::Named("cyan".into())),
+            "white" => Some(ColorSpec::Named("white".into())),
+            "brightBlack" | "bright_black" => Some(ColorSpec::Named("brightBlack".into())),
+            "brightRed" | "bright_red" => Some(ColorSpec::Named("brightRed".into())),
+            "brightGreen" | "bright_green" => Some(ColorSpec::Named("brightGreen".into())),
+            "brightYellow" | "bright_yellow" => Some(ColorSpec::Named("brightYellow".into())),
+            "brightBlue" | "bright_blue" => Some(ColorSpec::Named("brightBlue".into())),
+            "brightMagenta" | "bright_magenta" => Some(ColorSpec::Named("brightMagenta".into())),
+            "brightCyan" | "bright_cyan" => Some(ColorSpec::Named("brightCyan".into())),
+            "brightWhite" | "bright_white" => Some(ColorSpec::Named("brightWhite".into())),
             s if s.starts_with('#') && s.len() == 7 => {
-                let r = u8::from_str_radix(&s[1..3], 16).unwrap_or(0);
-                let g = u8::from_str_radix(&s[3..5], 16).unwrap_or(0);
-                let b = u8::from_str_radix(&s[5..7], 16).unwrap_or(0);
-                ColorSpec::Rgb(r, g, b)
+                let r = u8::from_str_radix(s.get(1..3)?, 16).ok()?;
+                let g = u8::from_str_radix(s.get(3..5)?, 16).ok()?;
+                let b = u8::from_str_radix(s.get(5..7)?, 16).ok()?;
+                Some(ColorSpec::Rgb(r, g, b))
             }
-            s if s.parse::<u8>().is_ok() => ColorSpec::Ansi256(s.parse().unwrap()),
-            _ => ColorSpec::Named("white".into()),
+            s => s.parse::<u8>().ok().map(ColorSpec::Ansi256),
         }
     }
 
@@ -223,3 +281,145 @@ impl Renderer {
         16 + 36 * ri + 6 * gi + bi
     }
 }
+
+/// `COLORFGBG` is `fg;bg` or `fg;xpm;bg` with ANSI color indexes; white,
+/// light gray and the bright colors other than black are light backgrounds.
+fn parse_colorfgbg(value: &str) -> Option<Background> {
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    Some(match bg {
+        7 | 9..=15 => Background::Light,
+        _ => Background::Dark,
+    })
+}
+
+/// Where `theme detect` saves the terminal background.
+fn background_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("claude-status").join("background"))
+}
+
+/// Ask the controlling terminal for its background color (OSC 11). None
+/// when there is no terminal or it doesn't answer in time. The reply is read
+/// with a deadline, one byte at a time, so nothing past it is consumed.
+#[cfg(unix)]
+fn query_terminal() -> Option<Background> {
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+        .open("/dev/tty")
+        .ok()?;
+    let fd = tty.as_raw_fd();
+
+    // Without raw mode the reply would be echoed and held until Enter
+    crossterm::terminal::enable_raw_mode().ok()?;
+    let reply = (|| {
+        tty.write_all(b"\x1b]11;?\x07")
+            .and_then(|_| tty.flush())
+            .ok()?;
+
+        let deadline = Instant::now() + BACKGROUND_QUERY_TIMEOUT;
+        let mut reply = Vec::new();
+        let mut byte = [0u8; 1];
+        while !(reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\")) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut pollfd = libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `pollfd` is a valid array of one entry for the call.
+            let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                return None;
+            }
+            match tty.read(&mut byte) {
+                Ok(1) => reply.push(byte[0]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+                _ => return None,
+            }
+        }
+        Some(reply)
+    })();
+    let _ = crossterm::terminal::disable_raw_mode();
+
+    parse_osc11(&String::from_utf8_lossy(&reply?))
+}
+
+#[cfg(not(unix))]
+fn query_terminal() -> Option<Background> {
+    None
+}
+
+/// Classify an OSC 11 reply, `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` with one to
+/// four hex digits per channel, by its relative luminance.
+fn parse_osc11(reply: &str) -> Option<Background> {
+    let rgb = reply.split_once("rgb:")?.1;
+    let rgb = rgb
+        .trim_end_matches(['\x07', '\\'])
+        .trim_end_matches('\x1b');
+    let channels: Vec<f64> = rgb
+        .split('/')
+        .map(|hex| {
+            if !(1..=4).contains(&hex.len()) {
+                return None;
+            }
+            let max = (1u32 << (4 * hex.len())) - 1;
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            Some(value as f64 / max as f64)
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = channels[..] else {
+        return None;
+    };
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colorfgbg_last_field_is_the_background() {
+        assert_eq!(parse_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(parse_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(parse_colorfgbg("0;default;7"), Some(Background::Light));
+        assert_eq!(parse_colorfgbg("7;8"), Some(Background::Dark));
+        assert_eq!(parse_colorfgbg("15;default"), None);
+        assert_eq!(parse_colorfgbg(""), None);
+    }
+
+    #[test]
+    fn test_osc11_reply_is_classified_by_luminance() {
+        let reply = |rgb: &str| format!("\x1b]11;rgb:{rgb}\x07");
+        assert_eq!(
+            parse_osc11(&reply("ffff/ffff/ffff")),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            parse_osc11(&reply("fdf6/f6f6/e3e3")),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            parse_osc11(&reply("2e2e/3434/4040")),
+            Some(Background::Dark)
+        );
+        assert_eq!(parse_osc11(&reply("00/00/00")), Some(Background::Dark));
+        // String terminator instead of BEL
+        assert_eq!(
+            parse_osc11("\x1b]11;rgb:ffff/ffff/ffff\x1b\\"),
+            Some(Background::Light)
+        );
+        assert_eq!(parse_osc11(&reply("ffff/ffff")), None);
+        assert_eq!(parse_osc11(&reply("zz/00/00")), None);
+        assert_eq!(parse_osc11(&reply("fffffffff/0/0")), None);
+        assert_eq!(parse_osc11(""), None);
+    }
+}
@@ -0,0 +1,162 @@
+use super::history::CostEvent;
+
+/// Length of a usage block.
+pub const BLOCK_SECS: i64 = 5 * 3600;
+
+/// Shortest stretch a block's spending is extrapolated from, so the first
+/// minutes of a block don't project absurd totals.
+const MIN_PROJECTION_SECS: i64 = 10 * 60;
+
+/// A 5-hour usage window. It opens at the first activity after the previous
+/// block ended, rounded down to the hour, and covers every session active
+/// during it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageBlock {
+    /// Unix seconds the block opened.
+    pub start: i64,
+    /// Unix seconds of the latest activity in the block.
+    pub last_activity: i64,
+    pub cost: f64,
+    pub tokens_input: u64,
+    pub tokens_output: u64,
+}
+
+impl UsageBlock {
+    fn starting_at(timestamp: i64) -> Self {
+        Self {
+            start: timestamp - timestamp.rem_euclid(3600),
+            last_activity: timestamp,
+            cost: 0.0,
+            tokens_input: 0,
+            tokens_output: 0,
+        }
+    }
+
+    /// Unix seconds the block closes.
+    pub fn end(&self) -> i64 {
+        self.start + BLOCK_SECS
+    }
+
+    pub fn remaining_secs(&self, now: i64) -> i64 {
+        (self.end() - now).max(0)
+    }
+
+    pub fn tokens(&self) -> u64 {
+        self.tokens_input + self.tokens_output
+    }
+
+    /// `amount` spent so far, extrapolated to the end of the block at the
+    /// rate since it opened.
+    pub fn project(&self, amount: f64, now: i64) -> f64 {
+        let elapsed = (now - self.start).clamp(MIN_PROJECTION_SECS, BLOCK_SECS);
+        amount * BLOCK_SECS as f64 / elapsed as f64
+    }
+}
+
+/// The block containing `now`, from usage events sorted by time. The render
+/// at `now` is itself activity, so there always is one; it is empty when
+/// `now` opened it.
+pub fn current_block(events: &[CostEvent], now: i64) -> UsageBlock {
+    let mut block = None::<UsageBlock>;
+    for event in events.iter().filter(|e| e.timestamp <= now) {
+        let block = match &mut block {
+            Some(b) if event.timestamp < b.end() => b,
+            _ => block.insert(UsageBlock::starting_at(event.timestamp)),
+        };
+        let (input, output) = event_tokens(event);
+        block.cost += event.cost;
+        block.tokens_input += input;
+        block.tokens_output += output;
+        block.last_activity = event.timestamp;
+    }
+
+    match block {
+        Some(mut b) if now < b.end() => {
+            b.last_activity = now;
+            b
+        }
+        _ => UsageBlock::starting_at(now),
+    }
+}
+
+/// Token counts recorded in a `usage` event's metadata.
+fn event_tokens(event: &CostEvent) -> (u64, u64) {
+    let Some(metadata) = event
+        .metadata
+        .as_deref()
+        .and_then(|m| serde_json::from_str::<serde_json::Value>(m).ok())
+    else {
+        return (0, 0);
+    };
+    let count = |key| metadata.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    (count("tokens_input"), count("tokens_output"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3600;
+    /// 2026-03-11 09:00 UTC
+    const NINE: i64 = 1_773_219_600;
+
+    fn event(timestamp: i64, cost: f64, tokens: u64) -> CostEvent {
+        CostEvent {
+            id: None,
+            session_id: format!("s{timestamp}"),
+            timestamp,
+            event_type: "usage".into(),
+            cost,
+            metadata: Some(format!(
+                r#"{{"tokens_input": {tokens}, "tokens_output": {}}}"#,
+                tokens / 10
+            )),
+        }
+    }
+
+    #[test]
+    fn test_block_starts_on_the_hour_of_first_activity() {
+        let events = [
+            event(NINE + 20 * 60, 1.0, 1000),
+            event(NINE + 2 * HOUR, 2.5, 3000),
+        ];
+        let block = current_block(&events, NINE + 3 * HOUR);
+        assert_eq!(block.start, NINE);
+        assert_eq!(block.end(), NINE + 5 * HOUR);
+        assert_eq!(block.remaining_secs(NINE + 3 * HOUR), 2 * HOUR);
+        assert_eq!(block.cost, 3.5);
+        assert_eq!((block.tokens_input, block.tokens_output), (4000, 400));
+        assert_eq!(block.last_activity, NINE + 3 * HOUR);
+    }
+
+    #[test]
+    fn test_activity_after_block_end_opens_next_block() {
+        let events = [
+            event(NINE, 10.0, 0),
+            // Opens a new block at 14:00, though 09:00's block had no gap
+            event(NINE + 5 * HOUR + 30 * 60, 1.0, 0),
+        ];
+        let block = current_block(&events, NINE + 6 * HOUR);
+        assert_eq!(block.start, NINE + 5 * HOUR);
+        assert_eq!(block.cost, 1.0);
+    }
+
+    #[test]
+    fn test_render_after_expired_block_opens_empty_block() {
+        let events = [event(NINE, 10.0, 500)];
+        let now = NINE + 7 * HOUR + 45 * 60;
+        let block = current_block(&events, now);
+        assert_eq!(block.start, NINE + 7 * HOUR);
+        assert_eq!(block.cost, 0.0);
+        assert_eq!(block.tokens(), 0);
+    }
+
+    #[test]
+    fn test_projection_extrapolates_rate_since_start() {
+        let block = current_block(&[event(NINE, 4.0, 0)], NINE + HOUR);
+        assert_eq!(block.project(block.cost, NINE + HOUR), 20.0);
+        // Early in a block the rate is taken over at least ten minutes
+        let block = current_block(&[event(NINE, 1.0, 0)], NINE + 60);
+        assert_eq!(block.project(block.cost, NINE + 60), 30.0);
+    }
+}
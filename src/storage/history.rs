@@ -1,6 +1,22 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
-use rusqlite::{params, Connection, Result as SqlResult};
+use rusqlite::{
+    Connection, OptionalExtension, Result as SqlResult, Row, TransactionBehavior, params,
+};
+use serde::Serialize;
+
+use super::blocks::{self, UsageBlock};
+use super::migrations;
+use crate::calendar::Timezone;
+use crate::widgets::SessionData;
+
+/// How long a writer waits for a concurrent status line process to release the lock.
+const BUSY_TIMEOUT_MS: u64 = 250;
+
+/// The `sessions` columns in the order `SessionRecord::from_row` reads them.
+const SESSION_COLUMNS: &str = "id, start_time, end_time, model, total_cost, \
+    tokens_input, tokens_output, tokens_cached, project, git_remote";
 
 /// A recorded session with aggregate cost data.
 #[derive(Debug, Clone)]
@@ -13,6 +29,119 @@ pub struct SessionRecord {
     pub tokens_input: u64,
     pub tokens_output: u64,
     pub tokens_cached: u64,
+    /// The session's project directory.
+    pub project: Option<String>,
+    /// The project's `origin` remote, without credentials.
+    pub git_remote: Option<String>,
+}
+
+/// How `CostTracker::cost_grouped` buckets sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Calendar day the session started on.
+    Day,
+    Model,
+    /// Project directory; sessions recorded before projects were tracked
+    /// share the key `(none)`.
+    Project,
+}
+
+impl GroupBy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "day" => Some(Self::Day),
+            "model" => Some(Self::Model),
+            "project" => Some(Self::Project),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Model => "model",
+            Self::Project => "project",
+        }
+    }
+}
+
+/// Aggregated session totals for one group.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostGroup {
+    pub key: String,
+    pub sessions: u64,
+    pub cost: f64,
+    pub tokens_input: u64,
+    pub tokens_output: u64,
+    pub tokens_cached: u64,
+}
+
+impl SessionRecord {
+    /// Build a session snapshot from the status line input, stamped with `now`.
+    /// Returns None when the input carries no session id.
+    pub fn from_session_data(data: &SessionData, now: i64) -> Option<Self> {
+        let id = data.session_id.clone().filter(|s| !s.is_empty())?;
+        let model = data
+            .model
+            .as_ref()
+            .and_then(|m| m.id.clone().or_else(|| m.display_name.clone()))
+            .unwrap_or_default();
+        let cw = data.context_window.as_ref();
+        let cached = cw
+            .and_then(|cw| cw.current_usage.as_ref())
+            .map(|u| {
+                u.cache_creation_input_tokens.unwrap_or(0) + u.cache_read_input_tokens.unwrap_or(0)
+            })
+            .unwrap_or(0);
+
+        Some(Self {
+            id,
+            start_time: now,
+            end_time: Some(now),
+            model,
+            total_cost: data
+                .cost
+                .as_ref()
+                .and_then(|c| c.total_cost_usd)
+                .unwrap_or(0.0),
+            tokens_input: cw.and_then(|cw| cw.total_input_tokens).unwrap_or(0),
+            tokens_output: cw.and_then(|cw| cw.total_output_tokens).unwrap_or(0),
+            tokens_cached: cached,
+            project: data
+                .workspace
+                .as_ref()
+                .and_then(|w| w.project_dir.clone())
+                .or_else(|| data.cwd.clone()),
+            git_remote: None,
+        })
+    }
+
+    fn from_row(row: &Row) -> SqlResult<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            start_time: row.get(1)?,
+            end_time: row.get(2)?,
+            model: row.get(3)?,
+            total_cost: row.get(4)?,
+            tokens_input: row.get::<_, i64>(5)? as u64,
+            tokens_output: row.get::<_, i64>(6)? as u64,
+            tokens_cached: row.get::<_, i64>(7)? as u64,
+            project: row.get(8)?,
+            git_remote: row.get(9)?,
+        })
+    }
+}
+
+/// What `CostTracker::check` found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbCheck {
+    pub version: u32,
+    /// Problems reported by SQLite's integrity check; empty when there are none.
+    pub integrity_errors: Vec<String>,
+    pub sessions: u64,
+    pub events: u64,
+    /// Events whose session is missing.
+    pub orphaned_events: u64,
 }
 
 /// A single cost event within a session.
@@ -32,28 +161,38 @@ pub struct CostTracker {
 }
 
 impl CostTracker {
-    /// Open (or create) the history database at the default location.
+    /// Open (or create) the history database at the default location and
+    /// bring its schema up to date.
     pub fn open() -> SqlResult<Self> {
+        let mut tracker = Self::open_unmigrated()?;
+        migrations::migrate(&mut tracker.conn)?;
+        Ok(tracker)
+    }
+
+    /// Open the history database at the default location as it is, for
+    /// inspecting or migrating it by hand.
+    pub fn open_unmigrated() -> SqlResult<Self> {
         let path = Self::db_path();
         if let Some(parent) = path.parent() {
             let _ = std::fs::create_dir_all(parent);
         }
         let conn = Connection::open(&path)?;
-        let tracker = Self { conn };
-        tracker.init_schema()?;
-        Ok(tracker)
+        // Several Claude sessions render concurrently; WAL lets readers proceed
+        // while one process writes, and the busy timeout serializes writers.
+        conn.busy_timeout(Duration::from_millis(BUSY_TIMEOUT_MS))?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
+        Ok(Self { conn })
     }
 
     /// Open an in-memory database (for testing).
-    #[cfg(test)]
     pub fn open_in_memory() -> SqlResult<Self> {
-        let conn = Connection::open_in_memory()?;
-        let tracker = Self { conn };
-        tracker.init_schema()?;
-        Ok(tracker)
+        let mut conn = Connection::open_in_memory()?;
+        migrations::migrate(&mut conn)?;
+        Ok(Self { conn })
     }
 
-    fn db_path() -> PathBuf {
+    /// Where the history database lives.
+    pub fn db_path() -> PathBuf {
         dirs::data_dir()
             .or_else(dirs::config_dir)
             .unwrap_or_else(|| PathBuf::from("."))
@@ -61,47 +200,64 @@ impl CostTracker {
             .join("history.db")
     }
 
-    fn init_schema(&self) -> SqlResult<()> {
-        self.conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                start_time INTEGER NOT NULL,
-                end_time INTEGER,
-                model TEXT NOT NULL,
-                total_cost REAL NOT NULL,
-                tokens_input INTEGER NOT NULL,
-                tokens_output INTEGER NOT NULL,
-                tokens_cached INTEGER NOT NULL
-            );
+    /// The database's schema version; see `SCHEMA_VERSION` for the latest.
+    pub fn schema_version(&self) -> SqlResult<u32> {
+        migrations::current(&self.conn)
+    }
 
-            CREATE TABLE IF NOT EXISTS events (
-                id INTEGER PRIMARY KEY,
-                session_id TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                event_type TEXT NOT NULL,
-                cost REAL NOT NULL,
-                metadata TEXT,
-                FOREIGN KEY (session_id) REFERENCES sessions(id)
-            );
+    /// Apply any pending migrations. Returns the version before and after.
+    pub fn migrate(&mut self) -> SqlResult<(u32, u32)> {
+        let from = migrations::migrate(&mut self.conn)?;
+        Ok((from, migrations::version(&self.conn)?))
+    }
 
-            CREATE INDEX IF NOT EXISTS idx_sessions_time ON sessions(start_time);
-            CREATE INDEX IF NOT EXISTS idx_events_session ON events(session_id);
-            CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp);",
-        )
+    /// Check the database's integrity and count what it holds.
+    pub fn check(&self) -> SqlResult<DbCheck> {
+        let mut stmt = self.conn.prepare("PRAGMA integrity_check")?;
+        let integrity_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .filter(|line| line != "ok")
+            .collect();
+        // Tables are missing from a database that was never migrated
+        let count = |sql: &str| {
+            self.conn
+                .query_row(sql, [], |row| row.get::<_, i64>(0))
+                .unwrap_or(0) as u64
+        };
+        Ok(DbCheck {
+            version: self.schema_version()?,
+            integrity_errors,
+            sessions: count("SELECT COUNT(*) FROM sessions"),
+            events: count("SELECT COUNT(*) FROM events"),
+            orphaned_events: count(
+                "SELECT COUNT(*) FROM events
+                 WHERE session_id NOT IN (SELECT id FROM sessions)",
+            ),
+        })
+    }
+
+    /// Fold the write-ahead log into the database and rebuild it without
+    /// unused pages.
+    pub fn vacuum(&self) -> SqlResult<()> {
+        self.conn
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")
     }
 
     /// Insert or update a session record.
     pub fn upsert_session(&self, session: &SessionRecord) -> SqlResult<()> {
         self.conn.execute(
-            "INSERT INTO sessions (id, start_time, end_time, model, total_cost, tokens_input, tokens_output, tokens_cached)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "INSERT INTO sessions (id, start_time, end_time, model, total_cost, tokens_input, tokens_output, tokens_cached, project, git_remote)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
              ON CONFLICT(id) DO UPDATE SET
                 end_time = excluded.end_time,
                 model = excluded.model,
                 total_cost = excluded.total_cost,
                 tokens_input = excluded.tokens_input,
                 tokens_output = excluded.tokens_output,
-                tokens_cached = excluded.tokens_cached",
+                tokens_cached = excluded.tokens_cached,
+                project = COALESCE(excluded.project, project),
+                git_remote = COALESCE(excluded.git_remote, git_remote)",
             params![
                 session.id,
                 session.start_time,
@@ -111,11 +267,84 @@ impl CostTracker {
                 session.tokens_input as i64,
                 session.tokens_output as i64,
                 session.tokens_cached as i64,
+                session.project,
+                session.git_remote,
             ],
         )?;
         Ok(())
     }
 
+    /// Record a status line snapshot for a session: upsert the session totals and,
+    /// if the cost went up since the last snapshot, append the delta as a `usage`
+    /// event. Returns the recorded cost delta (0.0 when nothing was appended).
+    ///
+    /// Runs in a single immediate transaction so concurrent renders of the same
+    /// session cannot both append the same delta.
+    pub fn record_session(&mut self, session: &SessionRecord) -> SqlResult<f64> {
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let previous: Option<(f64, i64, i64)> = tx
+            .query_row(
+                "SELECT total_cost, tokens_input, tokens_output FROM sessions WHERE id = ?1",
+                params![session.id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        let (prev_cost, prev_input, prev_output) = previous.unwrap_or((0.0, 0, 0));
+
+        tx.execute(
+            "INSERT INTO sessions (id, start_time, end_time, model, total_cost, tokens_input, tokens_output, tokens_cached, project, git_remote)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT(id) DO UPDATE SET
+                end_time = excluded.end_time,
+                model = excluded.model,
+                total_cost = MAX(total_cost, excluded.total_cost),
+                tokens_input = MAX(tokens_input, excluded.tokens_input),
+                tokens_output = MAX(tokens_output, excluded.tokens_output),
+                tokens_cached = excluded.tokens_cached,
+                project = COALESCE(excluded.project, project),
+                git_remote = COALESCE(excluded.git_remote, git_remote)",
+            params![
+                session.id,
+                session.start_time,
+                session.end_time,
+                session.model,
+                session.total_cost,
+                session.tokens_input as i64,
+                session.tokens_output as i64,
+                session.tokens_cached as i64,
+                session.project,
+                session.git_remote,
+            ],
+        )?;
+
+        let delta = session.total_cost - prev_cost;
+        if delta <= 0.0 {
+            tx.commit()?;
+            return Ok(0.0);
+        }
+
+        let metadata = serde_json::json!({
+            "model": session.model,
+            "tokens_input": (session.tokens_input as i64 - prev_input).max(0),
+            "tokens_output": (session.tokens_output as i64 - prev_output).max(0),
+        });
+        tx.execute(
+            "INSERT INTO events (session_id, timestamp, event_type, cost, metadata)
+             VALUES (?1, ?2, 'usage', ?3, ?4)",
+            params![
+                session.id,
+                session.end_time.unwrap_or(session.start_time),
+                delta,
+                metadata.to_string(),
+            ],
+        )?;
+        tx.commit()?;
+        Ok(delta)
+    }
+
     /// Record a cost event.
     pub fn insert_event(&self, event: &CostEvent) -> SqlResult<()> {
         self.conn.execute(
@@ -184,28 +413,17 @@ impl CostTracker {
     pub fn top_sessions(&self, from: i64, to: i64, limit: u32) -> Vec<SessionRecord> {
         let mut stmt = self
             .conn
-            .prepare(
-                "SELECT id, start_time, end_time, model, total_cost, tokens_input, tokens_output, tokens_cached
+            .prepare(&format!(
+                "SELECT {SESSION_COLUMNS}
                  FROM sessions WHERE start_time >= ?1 AND start_time < ?2
-                 ORDER BY total_cost DESC LIMIT ?3",
-            )
+                 ORDER BY total_cost DESC LIMIT ?3"
+            ))
             .unwrap();
 
-        stmt.query_map(params![from, to, limit], |row| {
-            Ok(SessionRecord {
-                id: row.get(0)?,
-                start_time: row.get(1)?,
-                end_time: row.get(2)?,
-                model: row.get(3)?,
-                total_cost: row.get(4)?,
-                tokens_input: row.get::<_, i64>(5)? as u64,
-                tokens_output: row.get::<_, i64>(6)? as u64,
-                tokens_cached: row.get::<_, i64>(7)? as u64,
-            })
-        })
-        .unwrap()
-        .filter_map(|r| r.ok())
-        .collect()
+        stmt.query_map(params![from, to, limit], SessionRecord::from_row)
+            .unwrap()
+            .filter_map(|r| r.ok())
+            .collect()
     }
 
     /// Count of sessions in a time range.
@@ -219,25 +437,92 @@ impl CostTracker {
             .unwrap_or(0) as u64
     }
 
+    /// Session cost and token totals in a time range, one row per group,
+    /// ordered by group key. Days are counted in `timezone`.
+    pub fn cost_grouped(
+        &self,
+        from: i64,
+        to: i64,
+        group_by: GroupBy,
+        timezone: Timezone,
+    ) -> Vec<CostGroup> {
+        let key = match (group_by, timezone) {
+            (GroupBy::Day, Timezone::Utc) => "strftime('%Y-%m-%d', start_time, 'unixepoch')".into(),
+            (GroupBy::Day, Timezone::Local) => {
+                "strftime('%Y-%m-%d', start_time, 'unixepoch', 'localtime')".into()
+            }
+            (GroupBy::Day, Timezone::Fixed(offset)) => format!(
+                "strftime('%Y-%m-%d', start_time + {}, 'unixepoch')",
+                offset.local_minus_utc()
+            ),
+            (GroupBy::Model, _) => "model".to_string(),
+            (GroupBy::Project, _) => "COALESCE(project, '(none)')".to_string(),
+        };
+        let sql = format!(
+            "SELECT {key} AS grp, COUNT(*), COALESCE(SUM(total_cost), 0.0),
+                    COALESCE(SUM(tokens_input), 0), COALESCE(SUM(tokens_output), 0),
+                    COALESCE(SUM(tokens_cached), 0)
+             FROM sessions WHERE start_time >= ?1 AND start_time < ?2
+             GROUP BY grp ORDER BY grp ASC"
+        );
+        let mut stmt = self.conn.prepare(&sql).unwrap();
+
+        stmt.query_map(params![from, to], |row| {
+            Ok(CostGroup {
+                key: row.get(0)?,
+                sessions: row.get::<_, i64>(1)? as u64,
+                cost: row.get(2)?,
+                tokens_input: row.get::<_, i64>(3)? as u64,
+                tokens_output: row.get::<_, i64>(4)? as u64,
+                tokens_cached: row.get::<_, i64>(5)? as u64,
+            })
+        })
+        .unwrap()
+        .filter_map(|r| r.ok())
+        .collect()
+    }
+
+    /// Cost of a project's sessions since a given timestamp (Unix seconds).
+    pub fn project_cost_since(&self, project: &str, since: i64) -> f64 {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(SUM(e.cost), 0.0) FROM events e
+                 JOIN sessions s ON s.id = e.session_id
+                 WHERE s.project = ?1 AND e.timestamp >= ?2",
+                params![project, since],
+                |row| row.get(0),
+            )
+            .unwrap_or(0.0)
+    }
+
+    /// The 5-hour usage block containing `now`, across every session.
+    pub fn current_block(&self, now: i64) -> UsageBlock {
+        // Activity after an idle stretch of a whole block always opens a new
+        // block, so the chain only needs to start at the last such stretch.
+        // A single pass over the ordered timestamps finds where it begins.
+        let chain_start = self
+            .conn
+            .query_row(
+                "SELECT ts FROM (
+                     SELECT timestamp AS ts, LAG(timestamp) OVER (ORDER BY timestamp) AS prev
+                     FROM events WHERE timestamp <= ?1
+                 )
+                 WHERE prev IS NULL OR ts - prev >= ?2
+                 ORDER BY ts DESC LIMIT 1",
+                params![now, blocks::BLOCK_SECS],
+                |row| row.get(0),
+            )
+            .unwrap_or(now);
+        blocks::current_block(&self.events_since(chain_start), now)
+    }
+
     /// Get the current session by session_id.
     pub fn get_session(&self, session_id: &str) -> Option<SessionRecord> {
         self.conn
             .query_row(
-                "SELECT id, start_time, end_time, model, total_cost, tokens_input, tokens_output, tokens_cached
-                 FROM sessions WHERE id = ?1",
+                &format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE id = ?1"),
                 params![session_id],
-                |row| {
-                    Ok(SessionRecord {
-                        id: row.get(0)?,
-                        start_time: row.get(1)?,
-                        end_time: row.get(2)?,
-                        model: row.get(3)?,
-                        total_cost: row.get(4)?,
-                        tokens_input: row.get::<_, i64>(5)? as u64,
-                        tokens_output: row.get::<_, i64>(6)? as u64,
-                        tokens_cached: row.get::<_, i64>(7)? as u64,
-                    })
-                },
+                SessionRecord::from_row,
             )
             .ok()
     }
@@ -260,6 +545,8 @@ mod tests {
             tokens_input: 5000,
             tokens_output: 1200,
             tokens_cached: 3000,
+            project: None,
+            git_remote: None,
         };
 
         tracker.upsert_session(&session).unwrap();
@@ -282,6 +569,8 @@ mod tests {
             tokens_input: 10000,
             tokens_output: 2000,
             tokens_cached: 5000,
+            project: None,
+            git_remote: None,
         };
         tracker.upsert_session(&session).unwrap();
 
@@ -320,6 +609,8 @@ mod tests {
                     tokens_input: 1000,
                     tokens_output: 200,
                     tokens_cached: 500,
+                    project: None,
+                    git_remote: None,
                 })
                 .unwrap();
         }
@@ -345,6 +636,8 @@ mod tests {
                 tokens_input: 0,
                 tokens_output: 0,
                 tokens_cached: 0,
+                project: None,
+                git_remote: None,
             })
             .unwrap();
         tracker
@@ -357,6 +650,8 @@ mod tests {
                 tokens_input: 0,
                 tokens_output: 0,
                 tokens_cached: 0,
+                project: None,
+                git_remote: None,
             })
             .unwrap();
 
@@ -366,4 +661,295 @@ mod tests {
         let cost = tracker.session_cost_range(0, 2000);
         assert!((cost - 15.0).abs() < 0.001);
     }
+
+    fn snapshot(id: &str, now: i64, cost: f64, input: u64) -> SessionRecord {
+        SessionRecord {
+            id: id.into(),
+            start_time: now,
+            end_time: Some(now),
+            model: "claude-opus-4-6".into(),
+            total_cost: cost,
+            tokens_input: input,
+            tokens_output: 100,
+            tokens_cached: 0,
+            project: None,
+            git_remote: None,
+        }
+    }
+
+    #[test]
+    fn test_record_session_appends_cost_deltas() {
+        let mut tracker = CostTracker::open_in_memory().unwrap();
+
+        assert!(
+            (tracker
+                .record_session(&snapshot("s1", 100, 0.25, 1000))
+                .unwrap()
+                - 0.25)
+                .abs()
+                < 1e-9
+        );
+        // Same cost again: no new event
+        assert_eq!(
+            tracker
+                .record_session(&snapshot("s1", 110, 0.25, 1000))
+                .unwrap(),
+            0.0
+        );
+        assert!(
+            (tracker
+                .record_session(&snapshot("s1", 120, 0.40, 1500))
+                .unwrap()
+                - 0.15)
+                .abs()
+                < 1e-9
+        );
+
+        let events = tracker.events_since(0);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].timestamp, 120);
+        assert!(
+            events[1]
+                .metadata
+                .as_deref()
+                .unwrap()
+                .contains("\"tokens_input\":500")
+        );
+        assert!((tracker.total_cost_since(0) - 0.40).abs() < 1e-9);
+
+        let session = tracker.get_session("s1").unwrap();
+        assert_eq!(session.start_time, 100);
+        assert_eq!(session.end_time, Some(120));
+        assert_eq!(session.tokens_input, 1500);
+    }
+
+    #[test]
+    fn test_current_block_chains_past_a_day_of_continuous_activity() {
+        let mut tracker = CostTracker::open_in_memory().unwrap();
+        // 2026-03-10 00:00 UTC, then hourly activity for 30 hours
+        let start = 1_773_100_800;
+        for hour in 0..=30 {
+            let ts = start + hour * 3600 + 20 * 60;
+            tracker
+                .record_session(&snapshot("s1", ts, (hour + 1) as f64, 1000))
+                .unwrap();
+        }
+
+        // Blocks open at 00:00, 05:00, ... 30:00; a chain started a day
+        // back would land on 27:00 instead
+        let now = start + 30 * 3600 + 30 * 60;
+        let block = tracker.current_block(now);
+        assert_eq!(block.start, start + 30 * 3600);
+        assert_eq!(block.cost, 1.0);
+
+        // An idle stretch of a whole block restarts the chain
+        let later = start + 40 * 3600 + 10 * 60;
+        tracker
+            .record_session(&snapshot("s1", later, 40.0, 1000))
+            .unwrap();
+        assert_eq!(tracker.current_block(later + 60).start, start + 40 * 3600);
+    }
+
+    #[test]
+    fn test_record_session_ignores_cost_regression() {
+        let mut tracker = CostTracker::open_in_memory().unwrap();
+        tracker
+            .record_session(&snapshot("s1", 100, 1.0, 1000))
+            .unwrap();
+        // A stale snapshot from a slower concurrent process must not lower totals
+        assert_eq!(
+            tracker
+                .record_session(&snapshot("s1", 105, 0.5, 500))
+                .unwrap(),
+            0.0
+        );
+
+        let session = tracker.get_session("s1").unwrap();
+        assert!((session.total_cost - 1.0).abs() < 1e-9);
+        assert_eq!(tracker.events_since(0).len(), 1);
+    }
+
+    #[test]
+    fn test_session_record_from_session_data() {
+        let data: SessionData = serde_json::from_str(
+            r#"{
+                "session_id": "abc",
+                "model": { "id": "claude-sonnet-4-5", "display_name": "Sonnet" },
+                "cwd": "/work/acme/src",
+                "workspace": { "project_dir": "/work/acme" },
+                "cost": { "total_cost_usd": 0.12 },
+                "context_window": {
+                    "total_input_tokens": 900,
+                    "total_output_tokens": 80,
+                    "current_usage": { "cache_read_input_tokens": 50, "cache_creation_input_tokens": 5 }
+                }
+            }"#,
+        )
+        .unwrap();
+        let record = SessionRecord::from_session_data(&data, 42).unwrap();
+        assert_eq!(record.id, "abc");
+        assert_eq!(record.model, "claude-sonnet-4-5");
+        assert_eq!(record.tokens_input, 900);
+        assert_eq!(record.tokens_cached, 55);
+        assert_eq!(record.end_time, Some(42));
+        assert_eq!(record.project.as_deref(), Some("/work/acme"));
+
+        assert!(SessionRecord::from_session_data(&SessionData::default(), 42).is_none());
+    }
+
+    #[test]
+    fn test_cost_grouped_by_day_and_model() {
+        let tracker = CostTracker::open_in_memory().unwrap();
+        let day = 86_400;
+        for (id, start, model, cost) in [
+            ("a", 100, "opus", 2.0),
+            ("b", 200, "sonnet", 1.0),
+            ("c", day + 100, "opus", 4.0),
+            ("d", 2 * day + 100, "opus", 8.0),
+        ] {
+            let mut record = snapshot(id, start, cost, 1000);
+            record.model = model.into();
+            tracker.upsert_session(&record).unwrap();
+        }
+
+        let by_day = tracker.cost_grouped(0, 2 * day, GroupBy::Day, Timezone::Utc);
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[0].key, "1970-01-01");
+        assert_eq!(by_day[0].sessions, 2);
+        assert!((by_day[0].cost - 3.0).abs() < 0.001);
+        assert_eq!(by_day[0].tokens_input, 2000);
+        assert_eq!(by_day[1].key, "1970-01-02");
+
+        let by_model = tracker.cost_grouped(0, 3 * day, GroupBy::Model, Timezone::Utc);
+        assert_eq!(by_model.len(), 2);
+        assert_eq!(by_model[0].key, "opus");
+        assert_eq!(by_model[0].sessions, 3);
+        assert!((by_model[0].cost - 14.0).abs() < 0.001);
+        assert_eq!(by_model[1].key, "sonnet");
+    }
+
+    #[test]
+    fn test_cost_by_project() {
+        let mut tracker = CostTracker::open_in_memory().unwrap();
+        for (id, now, cost, project) in [
+            ("a", 100, 2.0, Some("/work/acme")),
+            ("b", 200, 1.0, Some("/work/globex")),
+            ("a", 300, 5.0, Some("/work/acme")),
+            ("c", 400, 4.0, None),
+        ] {
+            let mut record = snapshot(id, now, cost, 1000);
+            record.project = project.map(String::from);
+            tracker.record_session(&record).unwrap();
+        }
+
+        let by_project = tracker.cost_grouped(0, 1000, GroupBy::Project, Timezone::Utc);
+        let keys: Vec<_> = by_project.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["(none)", "/work/acme", "/work/globex"]);
+        assert!((by_project[1].cost - 5.0).abs() < 0.001);
+
+        // Spend is counted from when it happened, not when the session started
+        assert!((tracker.project_cost_since("/work/acme", 0) - 5.0).abs() < 1e-9);
+        assert!((tracker.project_cost_since("/work/acme", 250) - 3.0).abs() < 1e-9);
+        assert_eq!(tracker.project_cost_since("/work/initech", 0), 0.0);
+    }
+
+    #[test]
+    fn test_remote_is_kept_once_recorded() {
+        let tracker = CostTracker::open_in_memory().unwrap();
+        let mut record = snapshot("s1", 100, 1.0, 1000);
+        record.project = Some("/work/acme".into());
+        record.git_remote = Some("git@github.com:acme/api.git".into());
+        tracker.upsert_session(&record).unwrap();
+
+        // Later renders don't look the remote up again
+        record.git_remote = None;
+        tracker.upsert_session(&record).unwrap();
+        let session = tracker.get_session("s1").unwrap();
+        assert_eq!(
+            session.git_remote.as_deref(),
+            Some("git@github.com:acme/api.git")
+        );
+    }
+
+    #[test]
+    fn test_check_reports_version_and_counts() {
+        let tracker = CostTracker::open_in_memory().unwrap();
+        tracker
+            .upsert_session(&snapshot("s1", 100, 1.0, 1000))
+            .unwrap();
+        tracker
+            .insert_event(&CostEvent {
+                id: None,
+                session_id: "s1".into(),
+                timestamp: 100,
+                event_type: "usage".into(),
+                cost: 1.0,
+                metadata: None,
+            })
+            .unwrap();
+        // Left behind by a build that didn't enforce foreign keys
+        tracker
+            .conn
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO events (session_id, timestamp, event_type, cost)
+                 VALUES ('gone', 100, 'usage', 1.0);
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+
+        let check = tracker.check().unwrap();
+        assert_eq!(check.version, migrations::SCHEMA_VERSION);
+        assert!(check.integrity_errors.is_empty());
+        assert_eq!((check.sessions, check.events), (1, 2));
+        assert_eq!(check.orphaned_events, 1);
+        tracker.vacuum().unwrap();
+    }
+
+    #[test]
+    fn test_migrated_database_keeps_old_sessions() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sessions (
+                id TEXT PRIMARY KEY,
+                start_time INTEGER NOT NULL,
+                end_time INTEGER,
+                model TEXT NOT NULL,
+                total_cost REAL NOT NULL,
+                tokens_input INTEGER NOT NULL,
+                tokens_output INTEGER NOT NULL,
+                tokens_cached INTEGER NOT NULL
+            );
+            INSERT INTO sessions VALUES ('old', 100, 200, 'opus', 3.0, 10, 20, 0);",
+        )
+        .unwrap();
+        let mut tracker = CostTracker { conn };
+        assert_eq!(tracker.schema_version().unwrap(), 1);
+        assert_eq!(tracker.migrate().unwrap(), (1, migrations::SCHEMA_VERSION));
+
+        let old = tracker.get_session("old").unwrap();
+        assert_eq!(old.project, None);
+        let mut record = snapshot("new", 300, 1.0, 1000);
+        record.project = Some("/work/acme".into());
+        tracker.upsert_session(&record).unwrap();
+        assert_eq!(
+            tracker.get_session("new").unwrap().project.as_deref(),
+            Some("/work/acme")
+        );
+    }
+
+    #[test]
+    fn test_cost_grouped_by_day_in_timezone() {
+        let tracker = CostTracker::open_in_memory().unwrap();
+        // 1970-01-02 03:00 UTC is still January 1st in UTC-8
+        tracker
+            .upsert_session(&snapshot("late", 86_400 + 3 * 3600, 1.0, 0))
+            .unwrap();
+
+        let utc = tracker.cost_grouped(0, 2 * 86_400, GroupBy::Day, Timezone::Utc);
+        assert_eq!(utc[0].key, "1970-01-02");
+        let pacific = Timezone::try_from("-08:00".to_string()).unwrap();
+        let local = tracker.cost_grouped(0, 2 * 86_400, GroupBy::Day, pacific);
+        assert_eq!(local[0].key, "1970-01-01");
+    }
 }
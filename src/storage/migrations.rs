@@ -0,0 +1,189 @@
+use rusqlite::{Connection, Result as SqlResult, TransactionBehavior};
+
+/// Schema changes, oldest first. A database's `PRAGMA user_version` is the
+/// number of them it has had applied. Append new changes; never edit one
+/// that has shipped.
+const MIGRATIONS: &[&str] = &[
+    // 1: sessions and their cost events
+    "CREATE TABLE IF NOT EXISTS sessions (
+        id TEXT PRIMARY KEY,
+        start_time INTEGER NOT NULL,
+        end_time INTEGER,
+        model TEXT NOT NULL,
+        total_cost REAL NOT NULL,
+        tokens_input INTEGER NOT NULL,
+        tokens_output INTEGER NOT NULL,
+        tokens_cached INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY,
+        session_id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        event_type TEXT NOT NULL,
+        cost REAL NOT NULL,
+        metadata TEXT,
+        FOREIGN KEY (session_id) REFERENCES sessions(id)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_sessions_time ON sessions(start_time);
+    CREATE INDEX IF NOT EXISTS idx_events_session ON events(session_id);
+    CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp);",
+    // 2: the project each session ran in
+    "ALTER TABLE sessions ADD COLUMN project TEXT;
+    ALTER TABLE sessions ADD COLUMN git_remote TEXT;
+    CREATE INDEX IF NOT EXISTS idx_sessions_project ON sessions(project);",
+];
+
+/// The schema version this build creates and understands.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// The schema version of a database.
+pub fn version(conn: &Connection) -> SqlResult<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring a database up to `SCHEMA_VERSION`, one migration per transaction.
+/// Returns the version it started at. Concurrent status line processes may
+/// race to migrate; the immediate transaction makes all but one wait, and
+/// they then find nothing left to do.
+pub fn migrate(conn: &mut Connection) -> SqlResult<u32> {
+    let start = current(conn)?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(start as usize) {
+        let target = i as u32 + 1;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        if current(&tx)? < target {
+            tx.execute_batch(sql)?;
+            tx.pragma_update(None, "user_version", target)?;
+        }
+        tx.commit()?;
+    }
+    // A database from before versioning may have needed nothing at all
+    if version(conn)? < start {
+        conn.pragma_update(None, "user_version", start)?;
+    }
+    Ok(start)
+}
+
+/// The schema version of a database, versioned or not.
+pub fn current(conn: &Connection) -> SqlResult<u32> {
+    match version(conn)? {
+        0 => unversioned(conn),
+        v => Ok(v),
+    }
+}
+
+/// The version of a database from before versioning, judged by its tables.
+fn unversioned(conn: &Connection) -> SqlResult<u32> {
+    let column_count = |name: &str| -> SqlResult<u32> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+    };
+    let has_sessions: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'sessions'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(if !has_sessions {
+        0
+    } else if column_count("project")? > 0 {
+        2
+    } else {
+        1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The schema as created before versioning was added.
+    const LEGACY_SCHEMA: &str = "CREATE TABLE sessions (
+            id TEXT PRIMARY KEY,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER,
+            model TEXT NOT NULL,
+            total_cost REAL NOT NULL,
+            tokens_input INTEGER NOT NULL,
+            tokens_output INTEGER NOT NULL,
+            tokens_cached INTEGER NOT NULL
+        );
+        CREATE TABLE events (
+            id INTEGER PRIMARY KEY,
+            session_id TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            event_type TEXT NOT NULL,
+            cost REAL NOT NULL,
+            metadata TEXT
+        );
+        INSERT INTO sessions VALUES ('old', 100, 200, 'opus', 3.0, 10, 20, 0);";
+
+    fn columns(conn: &Connection) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM pragma_table_info('sessions')")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_new_database_gets_latest_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&mut conn).unwrap(), 0);
+        assert_eq!(version(&conn).unwrap(), SCHEMA_VERSION);
+        assert!(columns(&conn).contains(&"project".to_string()));
+
+        // Nothing left to do the second time
+        assert_eq!(migrate(&mut conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_legacy_database_is_upgraded_in_place() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LEGACY_SCHEMA).unwrap();
+
+        assert_eq!(migrate(&mut conn).unwrap(), 1);
+        assert_eq!(version(&conn).unwrap(), SCHEMA_VERSION);
+        assert!(columns(&conn).contains(&"git_remote".to_string()));
+        let cost: f64 = conn
+            .query_row(
+                "SELECT total_cost FROM sessions WHERE id = 'old'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(cost, 3.0);
+    }
+
+    #[test]
+    fn test_unversioned_database_with_projects_is_not_altered_twice() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LEGACY_SCHEMA).unwrap();
+        conn.execute_batch(
+            "ALTER TABLE sessions ADD COLUMN project TEXT;
+             ALTER TABLE sessions ADD COLUMN git_remote TEXT;",
+        )
+        .unwrap();
+
+        assert_eq!(migrate(&mut conn).unwrap(), 2);
+        assert_eq!(version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_version_unchanged() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        // Migration 2 adds `project`, then fails on the existing `git_remote`
+        conn.execute_batch("CREATE TABLE sessions (id TEXT PRIMARY KEY, git_remote TEXT);")
+            .unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+
+        assert!(migrate(&mut conn).is_err());
+        assert_eq!(version(&conn).unwrap(), 1);
+        assert!(!columns(&conn).contains(&"project".to_string()));
+    }
+}
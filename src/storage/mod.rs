@@ -1,3 +1,7 @@
+mod blocks;
 mod history;
+mod migrations;
 
-pub use history::{CostEvent, CostTracker, SessionRecord};
+pub use blocks::{BLOCK_SECS, UsageBlock};
+pub use history::{CostEvent, CostGroup, CostTracker, DbCheck, GroupBy, SessionRecord};
+pub use migrations::SCHEMA_VERSION;
@@ -1,9 +1,34 @@
 use std::collections::HashMap;
 
+mod user;
+
+pub use user::UserTheme;
+
+/// Color roles every theme defines.
+pub const ROLES: &[&str] = &[
+    "model",
+    "context_ok",
+    "context_warn",
+    "context_critical",
+    "git_branch",
+    "git_clean",
+    "git_dirty",
+    "cost",
+    "duration",
+    "separator_fg",
+    "warning",
+    "danger",
+    "muted",
+    "accent",
+];
+
 #[derive(Debug, Clone)]
 pub struct Theme {
     pub name: String,
+    /// Foreground color per role.
     pub colors: HashMap<String, String>,
+    /// Background color per role, used for powerline segments.
+    pub backgrounds: HashMap<String, String>,
 }
 
 impl Theme {
@@ -23,6 +48,26 @@ impl Theme {
         }
     }
 
+    /// A user theme of that name if there is one, else a built-in theme.
+    pub fn load(name: &str) -> Self {
+        UserTheme::dir()
+            .and_then(|dir| UserTheme::find(&dir, name))
+            .map(|user| user.theme)
+            .unwrap_or_else(|| Self::get(name))
+    }
+
+    /// Built-in theme names followed by the user's own.
+    pub fn available() -> Vec<String> {
+        let mut names: Vec<String> = Self::list().into_iter().map(String::from).collect();
+        for theme in UserTheme::installed() {
+            if !names.contains(&theme.theme.name) {
+                names.push(theme.theme.name);
+            }
+        }
+        names
+    }
+
+    /// Built-in theme names.
     pub fn list() -> Vec<&'static str> {
         vec![
             "default",
@@ -43,216 +88,298 @@ impl Theme {
         self.colors.get(role).map(|s| s.as_str())
     }
 
-    pub fn role_for_widget(&self, widget_type: &str) -> Option<&str> {
+    pub fn background(&self, role: &str) -> Option<&str> {
+        self.backgrounds.get(role).map(|s| s.as_str())
+    }
+
+    /// The role a widget type is colored by.
+    pub fn role(widget_type: &str) -> Option<&'static str> {
         let role = match widget_type {
             "model" => "model",
             "context-percentage" | "context-length" => "context_ok",
-            "git-branch" => "git_branch",
+            "git-branch" | "git-worktree" => "git_branch",
             "git-status" => "git_clean",
-            "git-worktree" => "git_branch",
-            "session-cost" | "block-timer" => "cost",
-            "session-duration" | "api-duration" => "duration",
-            "separator" => "separator_fg",
+            "lines-changed" => "git_dirty",
+            "session-cost" | "turn-cost" | "cost-compare" | "block-timer" | "project-budget" => {
+                "cost"
+            }
+            "session-duration" | "api-duration" | "idle-time" => "duration",
+            "separator" | "flex-separator" => "separator_fg",
+            "cost-warning" | "burn-rate" => "warning",
+            "exceeds-tokens" => "danger",
+            "version" | "session-id" | "output-style" | "terminal-width" | "last-prompt" => "muted",
+            "tokens-input" | "tokens-output" | "tokens-cached" | "tokens-total" | "cwd"
+            | "vim-mode" | "agent-name" | "last-tool" | "tool-count" | "model-suggest"
+            | "custom-command" | "custom-text" | "template" => "accent",
             _ => return None,
         };
-        self.colors.get(role).map(|s| s.as_str())
+        Some(role)
     }
 
-    fn default_theme() -> Self {
+    /// Foreground color of a widget type's role.
+    pub fn role_for_widget(&self, widget_type: &str) -> Option<&str> {
+        self.color(Self::role(widget_type)?)
+    }
+
+    /// Background color of a widget type's role.
+    pub fn background_for_widget(&self, widget_type: &str) -> Option<&str> {
+        self.background(Self::role(widget_type)?)
+    }
+
+    /// Build a theme from `(role, foreground, background)` triples.
+    fn from_roles(name: &str, roles: &[(&str, &str, &str)]) -> Self {
         Self {
-            name: "default".into(),
-            colors: HashMap::from([
-                ("model".into(), "cyan".into()),
-                ("context_ok".into(), "green".into()),
-                ("context_warn".into(), "yellow".into()),
-                ("context_critical".into(), "red".into()),
-                ("git_branch".into(), "magenta".into()),
-                ("git_clean".into(), "green".into()),
-                ("git_dirty".into(), "yellow".into()),
-                ("cost".into(), "yellow".into()),
-                ("duration".into(), "white".into()),
-                ("separator_fg".into(), "brightBlack".into()),
-            ]),
+            name: name.into(),
+            colors: roles
+                .iter()
+                .map(|(role, fg, _)| (role.to_string(), fg.to_string()))
+                .collect(),
+            backgrounds: roles
+                .iter()
+                .map(|(role, _, bg)| (role.to_string(), bg.to_string()))
+                .collect(),
         }
     }
 
+    fn default_theme() -> Self {
+        Self::from_roles(
+            "default",
+            &[
+                ("model", "cyan", "238"),
+                ("context_ok", "green", "236"),
+                ("context_warn", "yellow", "236"),
+                ("context_critical", "red", "236"),
+                ("git_branch", "magenta", "239"),
+                ("git_clean", "green", "239"),
+                ("git_dirty", "yellow", "239"),
+                ("cost", "yellow", "237"),
+                ("duration", "white", "237"),
+                ("separator_fg", "brightBlack", "237"),
+                ("warning", "yellow", "236"),
+                ("danger", "red", "236"),
+                ("muted", "brightBlack", "237"),
+                ("accent", "blue", "238"),
+            ],
+        )
+    }
+
     fn solarized() -> Self {
-        Self {
-            name: "solarized".into(),
-            colors: HashMap::from([
-                ("model".into(), "#268bd2".into()),
-                ("context_ok".into(), "#859900".into()),
-                ("context_warn".into(), "#b58900".into()),
-                ("context_critical".into(), "#dc322f".into()),
-                ("git_branch".into(), "#6c71c4".into()),
-                ("git_clean".into(), "#859900".into()),
-                ("git_dirty".into(), "#cb4b16".into()),
-                ("cost".into(), "#b58900".into()),
-                ("duration".into(), "#93a1a1".into()),
-                ("separator_fg".into(), "#586e75".into()),
-            ]),
-        }
+        Self::from_roles(
+            "solarized",
+            &[
+                ("model", "#268bd2", "#073642"),
+                ("context_ok", "#859900", "#002b36"),
+                ("context_warn", "#b58900", "#002b36"),
+                ("context_critical", "#dc322f", "#002b36"),
+                ("git_branch", "#6c71c4", "#0a4b5a"),
+                ("git_clean", "#859900", "#0a4b5a"),
+                ("git_dirty", "#cb4b16", "#0a4b5a"),
+                ("cost", "#b58900", "#04313d"),
+                ("duration", "#93a1a1", "#04313d"),
+                ("separator_fg", "#586e75", "#04313d"),
+                ("warning", "#b58900", "#002b36"),
+                ("danger", "#dc322f", "#002b36"),
+                ("muted", "#586e75", "#04313d"),
+                ("accent", "#2aa198", "#073642"),
+            ],
+        )
     }
 
     fn nord() -> Self {
-        Self {
-            name: "nord".into(),
-            colors: HashMap::from([
-                ("model".into(), "#88c0d0".into()),
-                ("context_ok".into(), "#a3be8c".into()),
-                ("context_warn".into(), "#ebcb8b".into()),
-                ("context_critical".into(), "#bf616a".into()),
-                ("git_branch".into(), "#b48ead".into()),
-                ("git_clean".into(), "#a3be8c".into()),
-                ("git_dirty".into(), "#d08770".into()),
-                ("cost".into(), "#ebcb8b".into()),
-                ("duration".into(), "#d8dee9".into()),
-                ("separator_fg".into(), "#4c566a".into()),
-            ]),
-        }
+        Self::from_roles(
+            "nord",
+            &[
+                ("model", "#88c0d0", "#434c5e"),
+                ("context_ok", "#a3be8c", "#3b4252"),
+                ("context_warn", "#ebcb8b", "#3b4252"),
+                ("context_critical", "#bf616a", "#3b4252"),
+                ("git_branch", "#b48ead", "#4c566a"),
+                ("git_clean", "#a3be8c", "#4c566a"),
+                ("git_dirty", "#d08770", "#4c566a"),
+                ("cost", "#ebcb8b", "#2e3440"),
+                ("duration", "#d8dee9", "#2e3440"),
+                ("separator_fg", "#4c566a", "#2e3440"),
+                ("warning", "#ebcb8b", "#3b4252"),
+                ("danger", "#bf616a", "#3b4252"),
+                ("muted", "#616e88", "#2e3440"),
+                ("accent", "#81a1c1", "#434c5e"),
+            ],
+        )
     }
 
     fn dracula() -> Self {
-        Self {
-            name: "dracula".into(),
-            colors: HashMap::from([
-                ("model".into(), "#8be9fd".into()),
-                ("context_ok".into(), "#50fa7b".into()),
-                ("context_warn".into(), "#f1fa8c".into()),
-                ("context_critical".into(), "#ff5555".into()),
-                ("git_branch".into(), "#bd93f9".into()),
-                ("git_clean".into(), "#50fa7b".into()),
-                ("git_dirty".into(), "#ffb86c".into()),
-                ("cost".into(), "#f1fa8c".into()),
-                ("duration".into(), "#f8f8f2".into()),
-                ("separator_fg".into(), "#6272a4".into()),
-            ]),
-        }
+        Self::from_roles(
+            "dracula",
+            &[
+                ("model", "#8be9fd", "#44475a"),
+                ("context_ok", "#50fa7b", "#343746"),
+                ("context_warn", "#f1fa8c", "#343746"),
+                ("context_critical", "#ff5555", "#343746"),
+                ("git_branch", "#bd93f9", "#3c3f58"),
+                ("git_clean", "#50fa7b", "#3c3f58"),
+                ("git_dirty", "#ffb86c", "#3c3f58"),
+                ("cost", "#f1fa8c", "#282a36"),
+                ("duration", "#f8f8f2", "#282a36"),
+                ("separator_fg", "#6272a4", "#282a36"),
+                ("warning", "#ffb86c", "#343746"),
+                ("danger", "#ff5555", "#343746"),
+                ("muted", "#6272a4", "#282a36"),
+                ("accent", "#ff79c6", "#44475a"),
+            ],
+        )
     }
 
     fn gruvbox() -> Self {
-        Self {
-            name: "gruvbox".into(),
-            colors: HashMap::from([
-                ("model".into(), "#83a598".into()),
-                ("context_ok".into(), "#b8bb26".into()),
-                ("context_warn".into(), "#fabd2f".into()),
-                ("context_critical".into(), "#fb4934".into()),
-                ("git_branch".into(), "#d3869b".into()),
-                ("git_clean".into(), "#b8bb26".into()),
-                ("git_dirty".into(), "#fe8019".into()),
-                ("cost".into(), "#fabd2f".into()),
-                ("duration".into(), "#ebdbb2".into()),
-                ("separator_fg".into(), "#665c54".into()),
-            ]),
-        }
+        Self::from_roles(
+            "gruvbox",
+            &[
+                ("model", "#83a598", "#504945"),
+                ("context_ok", "#b8bb26", "#3c3836"),
+                ("context_warn", "#fabd2f", "#3c3836"),
+                ("context_critical", "#fb4934", "#3c3836"),
+                ("git_branch", "#d3869b", "#665c54"),
+                ("git_clean", "#b8bb26", "#665c54"),
+                ("git_dirty", "#fe8019", "#665c54"),
+                ("cost", "#fabd2f", "#32302f"),
+                ("duration", "#ebdbb2", "#32302f"),
+                ("separator_fg", "#665c54", "#32302f"),
+                ("warning", "#fabd2f", "#3c3836"),
+                ("danger", "#fb4934", "#3c3836"),
+                ("muted", "#928374", "#32302f"),
+                ("accent", "#8ec07c", "#504945"),
+            ],
+        )
     }
 
     fn monokai() -> Self {
-        Self {
-            name: "monokai".into(),
-            colors: HashMap::from([
-                ("model".into(), "#66d9ef".into()),
-                ("context_ok".into(), "#a6e22e".into()),
-                ("context_warn".into(), "#e6db74".into()),
-                ("context_critical".into(), "#f92672".into()),
-                ("git_branch".into(), "#ae81ff".into()),
-                ("git_clean".into(), "#a6e22e".into()),
-                ("git_dirty".into(), "#fd971f".into()),
-                ("cost".into(), "#e6db74".into()),
-                ("duration".into(), "#f8f8f2".into()),
-                ("separator_fg".into(), "#75715e".into()),
-            ]),
-        }
+        Self::from_roles(
+            "monokai",
+            &[
+                ("model", "#66d9ef", "#49483e"),
+                ("context_ok", "#a6e22e", "#3e3d32"),
+                ("context_warn", "#e6db74", "#3e3d32"),
+                ("context_critical", "#f92672", "#3e3d32"),
+                ("git_branch", "#ae81ff", "#575646"),
+                ("git_clean", "#a6e22e", "#575646"),
+                ("git_dirty", "#fd971f", "#575646"),
+                ("cost", "#e6db74", "#272822"),
+                ("duration", "#f8f8f2", "#272822"),
+                ("separator_fg", "#75715e", "#272822"),
+                ("warning", "#e6db74", "#3e3d32"),
+                ("danger", "#f92672", "#3e3d32"),
+                ("muted", "#75715e", "#272822"),
+                ("accent", "#fd971f", "#49483e"),
+            ],
+        )
     }
 
     fn light() -> Self {
-        Self {
-            name: "light".into(),
-            colors: HashMap::from([
-                ("model".into(), "#0550ae".into()),
-                ("context_ok".into(), "#116329".into()),
-                ("context_warn".into(), "#9a6700".into()),
-                ("context_critical".into(), "#cf222e".into()),
-                ("git_branch".into(), "#8250df".into()),
-                ("git_clean".into(), "#116329".into()),
-                ("git_dirty".into(), "#bc4c00".into()),
-                ("cost".into(), "#9a6700".into()),
-                ("duration".into(), "#24292f".into()),
-                ("separator_fg".into(), "#656d76".into()),
-            ]),
-        }
+        Self::from_roles(
+            "light",
+            &[
+                ("model", "#0550ae", "#dbe9f9"),
+                ("context_ok", "#116329", "#eaeef2"),
+                ("context_warn", "#9a6700", "#eaeef2"),
+                ("context_critical", "#cf222e", "#eaeef2"),
+                ("git_branch", "#8250df", "#e8dff7"),
+                ("git_clean", "#116329", "#e8dff7"),
+                ("git_dirty", "#bc4c00", "#e8dff7"),
+                ("cost", "#9a6700", "#f6f8fa"),
+                ("duration", "#24292f", "#f6f8fa"),
+                ("separator_fg", "#656d76", "#f6f8fa"),
+                ("warning", "#9a6700", "#eaeef2"),
+                ("danger", "#cf222e", "#eaeef2"),
+                ("muted", "#656d76", "#f6f8fa"),
+                ("accent", "#0969da", "#dbe9f9"),
+            ],
+        )
     }
 
     fn high_contrast() -> Self {
-        Self {
-            name: "high-contrast".into(),
-            colors: HashMap::from([
-                ("model".into(), "#71b7ff".into()),
-                ("context_ok".into(), "#3fb950".into()),
-                ("context_warn".into(), "#d29922".into()),
-                ("context_critical".into(), "#ff7b72".into()),
-                ("git_branch".into(), "#d2a8ff".into()),
-                ("git_clean".into(), "#3fb950".into()),
-                ("git_dirty".into(), "#f0883e".into()),
-                ("cost".into(), "#d29922".into()),
-                ("duration".into(), "#f0f6fc".into()),
-                ("separator_fg".into(), "#8b949e".into()),
-            ]),
-        }
+        Self::from_roles(
+            "high-contrast",
+            &[
+                ("model", "#71b7ff", "#000000"),
+                ("context_ok", "#3fb950", "#161b22"),
+                ("context_warn", "#d29922", "#161b22"),
+                ("context_critical", "#ff7b72", "#161b22"),
+                ("git_branch", "#d2a8ff", "#000000"),
+                ("git_clean", "#3fb950", "#000000"),
+                ("git_dirty", "#f0883e", "#000000"),
+                ("cost", "#d29922", "#161b22"),
+                ("duration", "#f0f6fc", "#161b22"),
+                ("separator_fg", "#8b949e", "#161b22"),
+                ("warning", "#d29922", "#161b22"),
+                ("danger", "#ff7b72", "#161b22"),
+                ("muted", "#8b949e", "#161b22"),
+                ("accent", "#56d4dd", "#000000"),
+            ],
+        )
     }
 
     fn one_dark() -> Self {
-        Self {
-            name: "one-dark".into(),
-            colors: HashMap::from([
-                ("model".into(), "#61afef".into()),
-                ("context_ok".into(), "#98c379".into()),
-                ("context_warn".into(), "#e5c07b".into()),
-                ("context_critical".into(), "#e06c75".into()),
-                ("git_branch".into(), "#c678dd".into()),
-                ("git_clean".into(), "#98c379".into()),
-                ("git_dirty".into(), "#d19a66".into()),
-                ("cost".into(), "#e5c07b".into()),
-                ("duration".into(), "#abb2bf".into()),
-                ("separator_fg".into(), "#5c6370".into()),
-            ]),
-        }
+        Self::from_roles(
+            "one-dark",
+            &[
+                ("model", "#61afef", "#3e4452"),
+                ("context_ok", "#98c379", "#2c323c"),
+                ("context_warn", "#e5c07b", "#2c323c"),
+                ("context_critical", "#e06c75", "#2c323c"),
+                ("git_branch", "#c678dd", "#4b5263"),
+                ("git_clean", "#98c379", "#4b5263"),
+                ("git_dirty", "#d19a66", "#4b5263"),
+                ("cost", "#e5c07b", "#282c34"),
+                ("duration", "#abb2bf", "#282c34"),
+                ("separator_fg", "#5c6370", "#282c34"),
+                ("warning", "#e5c07b", "#2c323c"),
+                ("danger", "#e06c75", "#2c323c"),
+                ("muted", "#5c6370", "#282c34"),
+                ("accent", "#56b6c2", "#3e4452"),
+            ],
+        )
     }
 
     fn tokyo_night() -> Self {
-        Self {
-            name: "tokyo-night".into(),
-            colors: HashMap::from([
-                ("model".into(), "#7aa2f7".into()),
-                ("context_ok".into(), "#9ece6a".into()),
-                ("context_warn".into(), "#e0af68".into()),
-                ("context_critical".into(), "#f7768e".into()),
-                ("git_branch".into(), "#bb9af7".into()),
-                ("git_clean".into(), "#9ece6a".into()),
-                ("git_dirty".into(), "#ff9e64".into()),
-                ("cost".into(), "#e0af68".into()),
-                ("duration".into(), "#c0caf5".into()),
-                ("separator_fg".into(), "#565f89".into()),
-            ]),
-        }
+        Self::from_roles(
+            "tokyo-night",
+            &[
+                ("model", "#7aa2f7", "#292e42"),
+                ("context_ok", "#9ece6a", "#24283b"),
+                ("context_warn", "#e0af68", "#24283b"),
+                ("context_critical", "#f7768e", "#24283b"),
+                ("git_branch", "#bb9af7", "#414868"),
+                ("git_clean", "#9ece6a", "#414868"),
+                ("git_dirty", "#ff9e64", "#414868"),
+                ("cost", "#e0af68", "#1a1b26"),
+                ("duration", "#c0caf5", "#1a1b26"),
+                ("separator_fg", "#565f89", "#1a1b26"),
+                ("warning", "#e0af68", "#24283b"),
+                ("danger", "#f7768e", "#24283b"),
+                ("muted", "#565f89", "#1a1b26"),
+                ("accent", "#7dcfff", "#292e42"),
+            ],
+        )
     }
 
     fn catppuccin() -> Self {
-        Self {
-            name: "catppuccin".into(),
-            colors: HashMap::from([
-                ("model".into(), "#89b4fa".into()),
-                ("context_ok".into(), "#a6e3a1".into()),
-                ("context_warn".into(), "#f9e2af".into()),
-                ("context_critical".into(), "#f38ba8".into()),
-                ("git_branch".into(), "#cba6f7".into()),
-                ("git_clean".into(), "#a6e3a1".into()),
-                ("git_dirty".into(), "#fab387".into()),
-                ("cost".into(), "#f9e2af".into()),
-                ("duration".into(), "#cdd6f4".into()),
-                ("separator_fg".into(), "#585b70".into()),
-            ]),
-        }
+        Self::from_roles(
+            "catppuccin",
+            &[
+                ("model", "#89b4fa", "#313244"),
+                ("context_ok", "#a6e3a1", "#181825"),
+                ("context_warn", "#f9e2af", "#181825"),
+                ("context_critical", "#f38ba8", "#181825"),
+                ("git_branch", "#cba6f7", "#45475a"),
+                ("git_clean", "#a6e3a1", "#45475a"),
+                ("git_dirty", "#fab387", "#45475a"),
+                ("cost", "#f9e2af", "#1e1e2e"),
+                ("duration", "#cdd6f4", "#1e1e2e"),
+                ("separator_fg", "#585b70", "#1e1e2e"),
+                ("warning", "#f9e2af", "#181825"),
+                ("danger", "#f38ba8", "#181825"),
+                ("muted", "#6c7086", "#1e1e2e"),
+                ("accent", "#94e2d5", "#313244"),
+            ],
+        )
     }
 }
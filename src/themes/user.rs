@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::{ROLES, Theme};
+use crate::config::Config;
+use crate::render::Renderer;
+
+/// A theme read from `themes/<name>.toml` next to the config file:
+///
+/// ```toml
+/// inherits = "nord"   # optional built-in base
+/// model = "#88c0d0"
+/// cost = { fg = "yellow", bg = "#3b4252" }
+/// ```
+#[derive(Debug, Clone)]
+pub struct UserTheme {
+    pub theme: Theme,
+    pub path: PathBuf,
+    pub inherits: Option<String>,
+    /// Problems found in the file. The theme is still usable; roles that are
+    /// missing or invalid fall back to the widgets' own colors.
+    pub errors: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ThemeFile {
+    inherits: Option<String>,
+    #[serde(flatten)]
+    roles: HashMap<String, RoleColors>,
+}
+
+/// A role's foreground alone, or a foreground/background pair.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RoleColors {
+    Fg(String),
+    Pair {
+        fg: Option<String>,
+        bg: Option<String>,
+    },
+}
+
+impl UserTheme {
+    /// Directory user themes are loaded from.
+    pub fn dir() -> Option<PathBuf> {
+        Config::default_path()?.parent().map(|d| d.join("themes"))
+    }
+
+    /// The user's themes from `dir()`, sorted by name.
+    pub fn installed() -> Vec<Self> {
+        Self::dir()
+            .map(|dir| Self::load_dir(&dir))
+            .unwrap_or_default()
+    }
+
+    /// All themes in `dir`, sorted by name.
+    pub fn load_dir(dir: &Path) -> Vec<Self> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut themes: Vec<Self> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .map(|p| Self::load(&p))
+            .collect();
+        themes.sort_by(|a, b| a.theme.name.cmp(&b.theme.name));
+        themes
+    }
+
+    /// The theme called `name` in `dir`, if there is a file for it.
+    pub fn find(dir: &Path, name: &str) -> Option<Self> {
+        let path = dir.join(format!("{name}.toml"));
+        path.is_file().then(|| Self::load(&path))
+    }
+
+    pub fn load(path: &Path) -> Self {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut theme = match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&name, &source),
+            Err(e) => Self::broken(&name, format!("cannot read file: {e}")),
+        };
+        theme.path = path.to_path_buf();
+        theme
+    }
+
+    /// Parse a theme file, resolving `inherits` and checking every role.
+    pub fn parse(name: &str, source: &str) -> Self {
+        let file: ThemeFile = match toml::from_str(source) {
+            Ok(f) => f,
+            Err(e) => return Self::broken(name, e.message().trim().to_string()),
+        };
+        let mut errors = Vec::new();
+
+        let mut theme = match file.inherits.as_deref() {
+            Some(base) if Theme::list().contains(&base) => Theme::get(base),
+            base => {
+                if let Some(base) = base {
+                    errors.push(format!(
+                        "unknown base theme \"{base}\" (available: {})",
+                        Theme::list().join(", ")
+                    ));
+                }
+                Self::empty(name)
+            }
+        };
+        theme.name = name.to_string();
+
+        let mut roles: Vec<_> = file.roles.into_iter().collect();
+        roles.sort_by(|a, b| a.0.cmp(&b.0));
+        for (role, value) in roles {
+            if !ROLES.contains(&role.as_str()) {
+                errors.push(format!("unknown role \"{role}\""));
+                continue;
+            }
+            let (fg, bg) = match value {
+                RoleColors::Fg(fg) => (Some(fg), None),
+                RoleColors::Pair { fg, bg } => (fg, bg),
+            };
+            for (color, kind, map) in [
+                (fg, "color", &mut theme.colors),
+                (bg, "background", &mut theme.backgrounds),
+            ] {
+                let Some(color) = color else { continue };
+                if Renderer::try_parse_color(&color).is_some() {
+                    map.insert(role.clone(), color);
+                } else {
+                    errors.push(format!("invalid {kind} \"{color}\" for role \"{role}\""));
+                }
+            }
+        }
+
+        for role in ROLES {
+            if !theme.colors.contains_key(*role) {
+                errors.push(format!("missing role \"{role}\""));
+            }
+        }
+
+        Self {
+            theme,
+            path: PathBuf::new(),
+            inherits: file.inherits,
+            errors,
+        }
+    }
+
+    fn empty(name: &str) -> Theme {
+        Theme {
+            name: name.to_string(),
+            colors: HashMap::new(),
+            backgrounds: HashMap::new(),
+        }
+    }
+
+    fn broken(name: &str, error: String) -> Self {
+        Self {
+            theme: Self::empty(name),
+            path: PathBuf::new(),
+            inherits: None,
+            errors: vec![error],
+        }
+    }
+}
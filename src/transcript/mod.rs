@@ -0,0 +1,278 @@
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::widgets::SessionData;
+
+/// What the transcript tells us about the session so far. Persisted between
+/// renders together with the byte offset that has already been consumed, so
+/// each render only parses lines appended since the previous one.
+///
+/// The prompt text is not persisted, since the cache lives in a shared
+/// directory; only where its line starts is, and `load` reads it back from
+/// the transcript.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptState {
+    pub offset: u64,
+    pub tool_count: u64,
+    pub last_tool: Option<String>,
+    #[serde(skip)]
+    pub last_prompt: Option<String>,
+    /// Byte offset of the line holding `last_prompt`.
+    #[serde(default)]
+    prompt_offset: Option<u64>,
+    pub last_assistant_at: Option<DateTime<Utc>>,
+}
+
+impl TranscriptState {
+    /// Consume everything appended to `path` since `self.offset`. Only
+    /// complete lines are consumed; a partially written trailing line is left
+    /// for the next call. A file shorter than the offset was rewritten, so the
+    /// state starts over from the beginning.
+    pub fn update(&mut self, path: &Path) -> std::io::Result<()> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            *self = Self::default();
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::with_capacity((len - self.offset) as usize);
+        file.take(len - self.offset).read_to_end(&mut buf)?;
+
+        let complete = match buf.iter().rposition(|&b| b == b'\n') {
+            Some(pos) => pos + 1,
+            None => return Ok(()),
+        };
+
+        let mut line_start = self.offset;
+        for line in buf[..complete].split_inclusive(|&b| b == b'\n') {
+            if self.ingest(&String::from_utf8_lossy(line)) {
+                self.prompt_offset = Some(line_start);
+            }
+            line_start += line.len() as u64;
+        }
+        self.offset += complete as u64;
+        Ok(())
+    }
+
+    /// Apply a single transcript line. Lines that are not valid JSON or carry
+    /// no message are ignored. Returns whether the line was a prompt.
+    pub fn ingest(&mut self, line: &str) -> bool {
+        let entry: Value = match serde_json::from_str(line.trim()) {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        let content = &entry["message"]["content"];
+
+        match entry["type"].as_str() {
+            Some("assistant") => {
+                if let Some(ts) = entry["timestamp"]
+                    .as_str()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                {
+                    self.last_assistant_at = Some(ts.with_timezone(&Utc));
+                }
+                for block in content.as_array().into_iter().flatten() {
+                    if block["type"] == "tool_use" {
+                        self.tool_count += 1;
+                        if let Some(name) = block["name"].as_str() {
+                            self.last_tool = Some(name.to_string());
+                        }
+                    }
+                }
+            }
+            Some("user") => {
+                if entry["isMeta"].as_bool().unwrap_or(false) {
+                    return false;
+                }
+                if let Some(prompt) = prompt_text(content) {
+                    self.last_prompt = Some(prompt);
+                    return true;
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Read `last_prompt` back from the transcript line it came from.
+    fn reload_prompt(&mut self, path: &Path) {
+        let Some(offset) = self.prompt_offset else {
+            return;
+        };
+        let mut line = String::new();
+        let read = File::open(path).and_then(|mut file| {
+            file.seek(SeekFrom::Start(offset))?;
+            BufReader::new(file).read_line(&mut line)
+        });
+        self.last_prompt = read
+            .ok()
+            .and_then(|_| serde_json::from_str::<Value>(line.trim()).ok())
+            .and_then(|entry| prompt_text(&entry["message"]["content"]));
+    }
+}
+
+/// The typed prompt in a user message. Tool results are also delivered as
+/// user messages; those carry no text blocks and are skipped.
+fn prompt_text(content: &Value) -> Option<String> {
+    let text = match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b["type"] == "text")
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => return None,
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() { None } else { Some(text) }
+}
+
+fn cache_path(session: &str, transcript: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(session.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(transcript.as_bytes());
+    let hash = hex::encode(&hasher.finalize()[..8]);
+    PathBuf::from(format!("/tmp/claude-status-transcript-{hash}"))
+}
+
+/// Load the transcript state for the session, reading only the part of the
+/// transcript appended since the last render. Returns None when the input has
+/// no transcript path or the transcript cannot be read.
+pub fn load(data: &SessionData) -> Option<TranscriptState> {
+    let transcript = data.transcript_path.as_deref().filter(|p| !p.is_empty())?;
+    let session = data.session_id.as_deref().unwrap_or_default();
+    let cache = cache_path(session, transcript);
+
+    let mut state: TranscriptState = fs::read_to_string(&cache)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let before = state.offset;
+    let path = Path::new(transcript);
+    state.update(path).ok()?;
+    if state.last_prompt.is_none() {
+        state.reload_prompt(path);
+    }
+
+    if state.offset != before
+        && let Ok(json) = serde_json::to_string(&state)
+    {
+        let _ = fs::write(&cache, json);
+    }
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_transcript(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "claude-status-transcript-test-{}-{name}.jsonl",
+            std::process::id()
+        ))
+    }
+
+    const PROMPT: &str =
+        r#"{"type":"user","message":{"role":"user","content":"fix   the\nbuild"}}"#;
+    const TOOL_USE: &str = r#"{"type":"assistant","timestamp":"2026-01-02T03:04:05Z","message":{"role":"assistant","content":[{"type":"text","text":"ok"},{"type":"tool_use","name":"Bash","input":{}}]}}"#;
+    const TOOL_RESULT: &str = r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","content":"done"}]}}"#;
+
+    #[test]
+    fn test_ingest_tracks_prompt_tools_and_timestamp() {
+        let mut state = TranscriptState::default();
+        state.ingest(PROMPT);
+        state.ingest(TOOL_USE);
+        state.ingest(TOOL_RESULT);
+        state.ingest("not json");
+
+        assert_eq!(state.last_prompt.as_deref(), Some("fix the build"));
+        assert_eq!(state.last_tool.as_deref(), Some("Bash"));
+        assert_eq!(state.tool_count, 1);
+        assert_eq!(
+            state.last_assistant_at.map(|t| t.to_rfc3339()),
+            Some("2026-01-02T03:04:05+00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn test_update_reads_only_appended_complete_lines() {
+        let path = temp_transcript("append");
+        fs::write(&path, format!("{PROMPT}\n{TOOL_USE}\n{TOOL_USE}")).unwrap();
+
+        let mut state = TranscriptState::default();
+        state.update(&path).unwrap();
+        assert_eq!(state.tool_count, 1, "partial trailing line is not consumed");
+        let offset = state.offset;
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file).unwrap();
+        state.update(&path).unwrap();
+        assert_eq!(state.tool_count, 2);
+        assert!(state.offset > offset);
+
+        state.update(&path).unwrap();
+        assert_eq!(state.tool_count, 2, "no new data, nothing re-counted");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_prompt_is_read_back_instead_of_cached() {
+        let path = temp_transcript("private");
+        fs::write(&path, format!("{TOOL_USE}\n{PROMPT}\n{TOOL_RESULT}\n")).unwrap();
+        let data = SessionData {
+            session_id: Some(format!("private-{}", std::process::id())),
+            transcript_path: Some(path.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let cache = cache_path(
+            data.session_id.as_deref().unwrap(),
+            data.transcript_path.as_deref().unwrap(),
+        );
+        let _ = fs::remove_file(&cache);
+
+        let state = load(&data).unwrap();
+        assert_eq!(state.last_prompt.as_deref(), Some("fix the build"));
+        assert!(!fs::read_to_string(&cache).unwrap().contains("fix"));
+
+        // A later render starts from the cache
+        let state = load(&data).unwrap();
+        assert_eq!(state.last_prompt.as_deref(), Some("fix the build"));
+        assert_eq!(state.tool_count, 1);
+
+        let _ = fs::remove_file(&cache);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_update_restarts_when_transcript_shrinks() {
+        let path = temp_transcript("shrink");
+        fs::write(&path, format!("{TOOL_USE}\n{TOOL_USE}\n")).unwrap();
+
+        let mut state = TranscriptState::default();
+        state.update(&path).unwrap();
+        assert_eq!(state.tool_count, 2);
+
+        fs::write(&path, format!("{PROMPT}\n")).unwrap();
+        state.update(&path).unwrap();
+        assert_eq!(state.tool_count, 0);
+        assert_eq!(state.last_tool, None);
+        assert_eq!(state.last_prompt.as_deref(), Some("fix the build"));
+
+        let _ = fs::remove_file(&path);
+    }
+}
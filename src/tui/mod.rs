@@ -67,6 +67,8 @@ pub struct TuiState {
     active_line: usize,
     // Theme tab state
     theme_cursor: usize,
+    /// Built-in and user theme names, read once at startup.
+    themes: Vec<String>,
     // Powerline tab state
     powerline_cursor: usize,
     // Layout tab state
@@ -83,6 +85,7 @@ impl TuiState {
             widget_cursor: 0,
             active_line: 0,
             theme_cursor: 0,
+            themes: Theme::available(),
             powerline_cursor: 0,
             layout_cursor: 0,
             modified: false,
@@ -235,7 +238,7 @@ fn handle_widgets_input(state: &mut TuiState, key: KeyCode) {
 }
 
 fn handle_theme_input(state: &mut TuiState, key: KeyCode) {
-    let themes = Theme::list();
+    let themes = &state.themes;
     match key {
         KeyCode::Up => {
             if state.theme_cursor > 0 {
@@ -249,7 +252,7 @@ fn handle_theme_input(state: &mut TuiState, key: KeyCode) {
         }
         KeyCode::Enter => {
             if let Some(name) = themes.get(state.theme_cursor) {
-                state.config.theme = name.to_string();
+                state.config.theme = name.clone();
                 state.modified = true;
             }
         }
@@ -523,6 +526,8 @@ fn available_widget_types() -> Vec<&'static str> {
         "tokens-cached",
         "tokens-total",
         "session-cost",
+        "turn-cost",
+        "cost-compare",
         "session-duration",
         "block-timer",
         "git-branch",
@@ -539,9 +544,15 @@ fn available_widget_types() -> Vec<&'static str> {
         "api-duration",
         "custom-command",
         "custom-text",
+        "template",
         "separator",
         "flex-separator",
+        "zone",
         "terminal-width",
+        "last-tool",
+        "tool-count",
+        "last-prompt",
+        "idle-time",
     ]
 }
 
@@ -556,5 +567,7 @@ fn default_widget(widget_type: &str) -> LineWidgetConfig {
         padding: None,
         merge_next: false,
         metadata: std::collections::HashMap::new(),
+        thresholds: std::collections::HashMap::new(),
+        when: None,
     }
 }
@@ -6,7 +6,7 @@ use ratatui::widgets::{Block, Borders, Paragraph};
 use crate::layout::LayoutEngine;
 use crate::render::Renderer;
 use crate::widgets::data::*;
-use crate::widgets::{SessionData, WidgetRegistry};
+use crate::widgets::{PluginWidget, SessionData, WidgetRegistry};
 
 use super::TuiState;
 
@@ -50,20 +50,24 @@ fn mock_session() -> SessionData {
         exceeds_200k_tokens: Some(false),
         vim: None,
         agent: None,
+        raw: None,
     }
 }
 
 pub fn draw_preview(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
     let data = mock_session();
     let renderer = Renderer::detect("none");
-    let registry = WidgetRegistry::new();
+    let mut registry = WidgetRegistry::builtin();
+    if let Some(dir) = PluginWidget::dir() {
+        registry.register_plugins(&dir);
+    }
 
     // Use a modified config with full flex mode for preview
     let mut preview_config = state.config.clone();
     preview_config.flex_mode = "compact".to_string();
 
     let engine = LayoutEngine::new(&preview_config, &renderer);
-    let rendered = engine.render(&data, &preview_config, &registry);
+    let rendered = engine.render(&data, &registry);
 
     let mut lines: Vec<Line> = vec![
         Line::from(Span::styled(
@@ -18,13 +18,13 @@ pub fn draw_theme_panel(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
 }
 
 fn draw_theme_list(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
-    let themes = Theme::list();
-    let items: Vec<ListItem> = themes
+    let items: Vec<ListItem> = state
+        .themes
         .iter()
         .enumerate()
         .map(|(i, name)| {
             let selected = i == state.theme_cursor;
-            let active = *name == state.config.theme.as_str();
+            let active = *name == state.config.theme;
             let marker = if selected { ">" } else { " " };
             let active_marker = if active { " *" } else { "" };
             let text = format!("{marker} {name}{active_marker}");
@@ -50,9 +50,12 @@ fn draw_theme_list(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
 }
 
 fn draw_theme_preview(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
-    let themes = Theme::list();
-    let theme_name = themes.get(state.theme_cursor).unwrap_or(&"default");
-    let theme = Theme::get(theme_name);
+    let theme_name = state
+        .themes
+        .get(state.theme_cursor)
+        .map(String::as_str)
+        .unwrap_or("default");
+    let theme = Theme::load(theme_name);
 
     let roles = [
         ("model", "Model color"),
@@ -65,6 +68,10 @@ fn draw_theme_preview(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
         ("cost", "Cost"),
         ("duration", "Duration"),
         ("separator_fg", "Separator"),
+        ("warning", "Warning"),
+        ("danger", "Danger"),
+        ("muted", "Muted"),
+        ("accent", "Accent"),
     ];
 
     let lines: Vec<Line> = roles
@@ -72,10 +79,23 @@ fn draw_theme_preview(f: &mut ratatui::Frame, state: &TuiState, area: Rect) {
         .map(|(role, label)| {
             let color_str = theme.color(role).unwrap_or("(none)");
             let fg_color = parse_preview_color(color_str);
-            Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!("  {label}: "), Style::default().fg(Color::White)),
                 Span::styled(format!("████ {color_str}"), Style::default().fg(fg_color)),
-            ])
+            ];
+            // Powerline background, shown behind a sample of the foreground
+            if let Some(bg) = theme.background(role) {
+                spans.push(Span::raw("  "));
+                spans.push(Span::styled(
+                    " Aa ",
+                    Style::default().fg(fg_color).bg(parse_preview_color(bg)),
+                ));
+                spans.push(Span::styled(
+                    format!(" on {bg}"),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
         })
         .collect();
 
@@ -92,6 +112,8 @@ fn parse_preview_color(s: &str) -> Color {
         let g = u8::from_str_radix(&s[3..5], 16).unwrap_or(255);
         let b = u8::from_str_radix(&s[5..7], 16).unwrap_or(255);
         Color::Rgb(r, g, b)
+    } else if let Ok(index) = s.parse::<u8>() {
+        Color::Indexed(index)
     } else {
         match s {
             "black" => Color::Black,
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct AgentNameWidget;
@@ -8,7 +9,12 @@ impl Widget for AgentNameWidget {
         "agent-name"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
         let agent = match &data.agent {
             Some(a) => a,
             None => {
@@ -18,6 +24,7 @@ impl Widget for AgentNameWidget {
                     priority: 85,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -30,16 +37,17 @@ impl Widget for AgentNameWidget {
                 priority: 85,
                 visible: false,
                 color_hint: None,
+                value: None,
             };
         }
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 85,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
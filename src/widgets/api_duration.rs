@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct ApiDurationWidget;
@@ -8,7 +9,12 @@ impl Widget for ApiDurationWidget {
         "api-duration"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let cost = match &data.cost {
             Some(c) => c,
             None => {
@@ -18,6 +24,7 @@ impl Widget for ApiDurationWidget {
                     priority: 35,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -31,6 +38,7 @@ impl Widget for ApiDurationWidget {
                     priority: 35,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -44,6 +52,7 @@ impl Widget for ApiDurationWidget {
                     priority: 35,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -57,13 +66,13 @@ impl Widget for ApiDurationWidget {
             format!("API: {}", pct_str)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 35,
             visible: true,
             color_hint: None,
+            value: Some(pct as f64),
         }
     }
 }
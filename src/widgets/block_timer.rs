@@ -1,12 +1,16 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
+use super::tokens::format_tokens;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::storage::{BLOCK_SECS, UsageBlock};
 
-const BLOCK_DURATION_MS: u64 = 18_000_000; // 5 hours
-
+/// Shows the 5-hour usage block shared by every session on the machine:
+/// time left, optionally what it has used, and a warning when spending at
+/// the current rate would pass `cost_cap` or `token_cap` by its end.
 pub struct BlockTimerWidget;
 
-fn format_hm(ms: u64) -> String {
-    let total_mins = ms / 60_000;
+fn format_hm(secs: i64) -> String {
+    let total_mins = secs / 60;
     let hours = total_mins / 60;
     let mins = total_mins % 60;
     if hours > 0 {
@@ -16,54 +20,106 @@ fn format_hm(ms: u64) -> String {
     }
 }
 
+/// The current block from recorded history, topped up with this session's
+/// usage that hasn't been recorded yet. Without a history database, the
+/// session's own duration is the best guess.
+fn current_block(data: &SessionData, ctx: &RenderContext) -> Option<UsageBlock> {
+    let cost = data.cost.as_ref()?;
+    let now = ctx.now().timestamp();
+    let session_cost = cost.total_cost_usd.unwrap_or(0.0);
+    let cw = data.context_window.as_ref();
+    let session_input = cw.and_then(|c| c.total_input_tokens).unwrap_or(0);
+    let session_output = cw.and_then(|c| c.total_output_tokens).unwrap_or(0);
+
+    let Some(tracker) = ctx.tracker() else {
+        let elapsed = (cost.total_duration_ms? / 1000) as i64 % BLOCK_SECS;
+        return Some(UsageBlock {
+            start: now - elapsed,
+            last_activity: now,
+            cost: session_cost,
+            tokens_input: session_input,
+            tokens_output: session_output,
+        });
+    };
+
+    let mut block = tracker.current_block(now);
+    let recorded = data
+        .session_id
+        .as_deref()
+        .and_then(|id| tracker.get_session(id));
+    let (cost, input, output) = recorded
+        .map(|r| (r.total_cost, r.tokens_input, r.tokens_output))
+        .unwrap_or_default();
+    block.cost += (session_cost - cost).max(0.0);
+    block.tokens_input += session_input.saturating_sub(input);
+    block.tokens_output += session_output.saturating_sub(output);
+    Some(block)
+}
+
 impl Widget for BlockTimerWidget {
     fn name(&self) -> &str {
         "block-timer"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
-        let cost = match &data.cost {
-            Some(c) => c,
-            None => {
-                return WidgetOutput {
-                    text: String::new(),
-                    display_width: 0,
-                    priority: 55,
-                    visible: false,
-                    color_hint: None,
-                };
-            }
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["bar", "bar_width", "details", "cost_cap", "token_cap"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let Some(block) = current_block(data, ctx) else {
+            return WidgetOutput {
+                text: String::new(),
+                display_width: 0,
+                priority: 55,
+                visible: false,
+                color_hint: None,
+                value: None,
+            };
         };
 
-        let duration_ms = match cost.total_duration_ms {
-            Some(d) => d,
-            None => {
-                return WidgetOutput {
-                    text: String::new(),
-                    display_width: 0,
-                    priority: 55,
-                    visible: false,
-                    color_hint: None,
-                };
-            }
+        let now = ctx.now().timestamp();
+        let elapsed = (now - block.start).clamp(0, BLOCK_SECS);
+        let remaining_str = format_hm(block.remaining_secs(now));
+        let flag = |key: &str| {
+            config
+                .metadata
+                .get(key)
+                .map(|v| v == "true")
+                .unwrap_or(false)
         };
+        let cap = |key: &str| config.metadata.get(key).and_then(|v| v.parse::<f64>().ok());
 
-        let block_elapsed = duration_ms % BLOCK_DURATION_MS;
-        let block_remaining = BLOCK_DURATION_MS - block_elapsed;
-        let remaining_str = format_hm(block_remaining);
+        // The first cap that spending at this rate would pass
+        let projected_cost = block.project(block.cost, now);
+        let projected_tokens = block.project(block.tokens() as f64, now);
+        let over_cap = if let Some(limit) = cap("cost_cap").filter(|&c| projected_cost > c) {
+            Some(format!("~${:.2} > ${:.2}", projected_cost, limit))
+        } else {
+            cap("token_cap")
+                .filter(|&c| projected_tokens > c)
+                .map(|limit| {
+                    format!(
+                        "~{} > {} tok",
+                        format_tokens(projected_tokens as u64, true),
+                        format_tokens(limit as u64, true)
+                    )
+                })
+        };
 
-        let text = if config
-            .metadata
-            .get("bar")
-            .map(|v| v == "true")
-            .unwrap_or(false)
-        {
+        let mut text = if config.compact {
+            remaining_str
+        } else if flag("bar") {
             let bar_width: usize = config
                 .metadata
                 .get("bar_width")
                 .and_then(|w| w.parse().ok())
                 .unwrap_or(16);
-            let fraction = block_elapsed as f64 / BLOCK_DURATION_MS as f64;
+            let fraction = elapsed as f64 / BLOCK_SECS as f64;
             let filled = (fraction * bar_width as f64).round() as usize;
             let filled = filled.min(bar_width);
             let empty = bar_width - filled;
@@ -73,17 +129,34 @@ impl Widget for BlockTimerWidget {
                 "░".repeat(empty),
                 remaining_str
             )
+        } else if flag("details") {
+            let start = ctx.calendar().timezone.format(block.start, "%H:%M");
+            format!(
+                "Block {}: {} left, ${:.2}, {} tok",
+                start,
+                remaining_str,
+                block.cost,
+                format_tokens(block.tokens(), true)
+            )
         } else {
             format!("Block: {} left", remaining_str)
         };
 
-        let display_width = text.len();
+        if let Some(warning) = &over_cap {
+            if config.compact {
+                text.push_str(" \u{26A0}");
+            } else {
+                text.push_str(&format!(" \u{26A0} {warning}"));
+            }
+        }
+
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 55,
             visible: true,
-            color_hint: None,
+            color_hint: over_cap.map(|_| "red".into()),
+            value: Some(elapsed as f64 / BLOCK_SECS as f64 * 100.0),
         }
     }
 }
@@ -1,8 +1,7 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
-use crate::storage::CostTracker;
-
-use chrono::Utc;
+use crate::calendar::{Calendar, Period};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BurnStatus {
@@ -26,9 +25,17 @@ impl BurnStatus {
 pub struct BurnRateWidget;
 
 impl BurnRateWidget {
-    fn calculate(window_minutes: u32, weekly_limit: f64) -> Option<(f64, BurnStatus, f64)> {
-        let tracker = CostTracker::open().ok()?;
-        let now = Utc::now().timestamp();
+    /// The hourly rate over the window, its status, and the hours until the
+    /// rest of this week's limit is spent at that rate; infinite when the
+    /// week resets first.
+    fn calculate(
+        ctx: &RenderContext,
+        calendar: &Calendar,
+        window_minutes: u32,
+        weekly_limit: f64,
+    ) -> Option<(f64, BurnStatus, f64)> {
+        let tracker = ctx.tracker()?;
+        let now = ctx.now().timestamp();
         let window_secs = window_minutes as i64 * 60;
         let since = now - window_secs;
 
@@ -55,8 +62,14 @@ impl BurnRateWidget {
             BurnStatus::Critical
         };
 
-        let hours_until_limit = if rate_per_hour > 0.0 {
-            weekly_limit / rate_per_hour
+        // What's left of this week's limit, and of the week
+        let week_start = calendar.start(Period::Week, ctx.now());
+        let week_end = calendar.end(Period::Week, ctx.now());
+        let left = (weekly_limit - tracker.total_cost_since(week_start)).max(0.0);
+        let hours_until_reset = (week_end - now) as f64 / 3600.0;
+        let hours_until_limit = left / rate_per_hour;
+        let hours_until_limit = if hours_until_limit < hours_until_reset {
+            hours_until_limit
         } else {
             f64::INFINITY
         };
@@ -70,15 +83,25 @@ impl Widget for BurnRateWidget {
         "burn-rate"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["weekly_limit", "window_minutes"]
+    }
+
+    fn render(
+        &self,
+        _data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         // Pro-only: gracefully hidden if not Pro
-        if !crate::license::is_pro() {
+        if !ctx.is_pro() {
             return WidgetOutput {
                 text: String::new(),
                 display_width: 0,
                 priority: 65,
                 visible: false,
                 color_hint: None,
+                value: None,
             };
         }
 
@@ -94,38 +117,44 @@ impl Widget for BurnRateWidget {
             .and_then(|v| v.parse().ok())
             .unwrap_or(200.0);
 
-        let (rate, status, hours_left) = match Self::calculate(window_minutes, weekly_limit) {
-            Some(v) => v,
-            None => {
-                return WidgetOutput {
-                    text: String::new(),
-                    display_width: 0,
-                    priority: 65,
-                    visible: false,
-                    color_hint: None,
-                };
-            }
-        };
+        let (rate, status, hours_left) =
+            match Self::calculate(ctx, ctx.calendar(), window_minutes, weekly_limit) {
+                Some(v) => v,
+                None => {
+                    return WidgetOutput {
+                        text: String::new(),
+                        display_width: 0,
+                        priority: 65,
+                        visible: false,
+                        color_hint: None,
+                        value: None,
+                    };
+                }
+            };
 
         let text = if config.raw_value {
             format!("{:.2}", rate)
         } else if rate < 0.01 {
             "Burn: idle".into()
-        } else if hours_left.is_infinite() || hours_left > 168.0 {
+        } else if config.compact {
+            format!("${:.2}/hr", rate)
+        } else if hours_left.is_infinite() {
             format!("Burn: ${:.2}/hr", rate)
+        } else if hours_left == 0.0 {
+            format!("Burn: ${:.2}/hr -> limit reached", rate)
         } else {
             let hours = hours_left as u64;
             let mins = ((hours_left - hours as f64) * 60.0) as u64;
             format!("Burn: ${:.2}/hr -> limit in {}h {}m", rate, hours, mins)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 65,
             visible: true,
             color_hint: status.color_hint(),
+            value: Some(rate),
         }
     }
 }
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 fn context_color_hint(pct: f64) -> Option<String> {
@@ -18,7 +19,16 @@ impl Widget for ContextPercentageWidget {
         "context-percentage"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["bar", "inverse"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let cw = match &data.context_window {
             Some(cw) => cw,
             None => {
@@ -28,6 +38,7 @@ impl Widget for ContextPercentageWidget {
                     priority: 85,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -41,6 +52,7 @@ impl Widget for ContextPercentageWidget {
                     priority: 85,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -75,13 +87,13 @@ impl Widget for ContextPercentageWidget {
             format!("{}%", display_pct as u64)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 85,
             visible: true,
             color_hint: context_color_hint(pct),
+            value: Some(display_pct),
         }
     }
 }
@@ -105,7 +117,12 @@ impl Widget for ContextLengthWidget {
         "context-length"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let cw = match &data.context_window {
             Some(cw) => cw,
             None => {
@@ -115,6 +132,7 @@ impl Widget for ContextLengthWidget {
                     priority: 60,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -130,6 +148,7 @@ impl Widget for ContextLengthWidget {
                     priority: 60,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -144,13 +163,13 @@ impl Widget for ContextLengthWidget {
             Self::format_compact(total)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 60,
             visible: true,
             color_hint: context_color_hint(pct),
+            value: Some(total as f64),
         }
     }
 }
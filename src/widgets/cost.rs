@@ -1,14 +1,26 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct SessionCostWidget;
+pub struct TurnCostWidget;
+pub struct CostCompareWidget;
 
 impl Widget for SessionCostWidget {
     fn name(&self) -> &str {
         "session-cost"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["burn_rate"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let cost = match &data.cost {
             Some(c) => c,
             None => {
@@ -18,6 +30,7 @@ impl Widget for SessionCostWidget {
                     priority: 70,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -31,13 +44,14 @@ impl Widget for SessionCostWidget {
                     priority: 70,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
         let cost_str = format!("${:.2}", total_usd);
 
-        let text = if config.raw_value {
+        let text = if config.raw_value || config.compact {
             cost_str
         } else if config
             .metadata
@@ -60,13 +74,120 @@ impl Widget for SessionCostWidget {
             cost_str
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 70,
             visible: true,
             color_hint: None,
+            value: Some(total_usd),
+        }
+    }
+}
+
+impl Widget for TurnCostWidget {
+    fn name(&self) -> &str {
+        "turn-cost"
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let cost = match ctx.pricing().turn_cost(data) {
+            Some(c) => c,
+            None => {
+                return WidgetOutput {
+                    text: String::new(),
+                    display_width: 0,
+                    priority: 45,
+                    visible: false,
+                    color_hint: None,
+                    value: None,
+                };
+            }
+        };
+
+        let cost_str = format!("${:.3}", cost);
+        let text = if config.raw_value || config.compact {
+            cost_str
+        } else {
+            format!("Turn: {}", cost_str)
+        };
+
+        WidgetOutput {
+            text,
+            display_width: 0,
+            priority: 45,
+            visible: true,
+            color_hint: None,
+            value: Some(cost),
+        }
+    }
+}
+
+impl Widget for CostCompareWidget {
+    fn name(&self) -> &str {
+        "cost-compare"
+    }
+
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["model"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let target = config
+            .metadata
+            .get("model")
+            .map(|s| s.as_str())
+            .unwrap_or("sonnet");
+
+        let already_on_target = data
+            .model
+            .as_ref()
+            .and_then(|m| m.id.as_deref().or(m.display_name.as_deref()))
+            .map(|m| m.to_lowercase().contains(&target.to_lowercase()))
+            .unwrap_or(false);
+
+        let cost = match ctx.pricing().cost_on(data, target) {
+            Some(c) if !already_on_target => c,
+            _ => {
+                return WidgetOutput {
+                    text: String::new(),
+                    display_width: 0,
+                    priority: 40,
+                    visible: false,
+                    color_hint: None,
+                    value: None,
+                };
+            }
+        };
+
+        let cost_str = format!("${:.2}", cost);
+        let text = if config.raw_value || config.compact {
+            cost_str
+        } else {
+            let mut label = target.to_string();
+            if let Some(first) = label.get_mut(..1) {
+                first.make_ascii_uppercase();
+            }
+            format!("{}: {}", label, cost_str)
+        };
+
+        WidgetOutput {
+            text,
+            display_width: 0,
+            priority: 40,
+            visible: true,
+            color_hint: None,
+            value: Some(cost),
         }
     }
 }
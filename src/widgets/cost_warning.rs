@@ -1,28 +1,18 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
-use crate::storage::CostTracker;
-
-use chrono::{Datelike, Utc};
+use crate::calendar::{Calendar, Period};
 
 pub struct CostWarningWidget;
 
 impl CostWarningWidget {
-    /// Calculate the start of the current week (Monday 00:00 UTC) as Unix timestamp.
-    fn week_start() -> i64 {
-        let now = Utc::now();
-        let days_since_monday = now.weekday().num_days_from_monday() as i64;
-        let start_of_today = now
-            .date_naive()
-            .and_hms_opt(0, 0, 0)
-            .unwrap()
-            .and_utc()
-            .timestamp();
-        start_of_today - (days_since_monday * 86400)
-    }
-
-    fn calculate(weekly_limit: f64) -> Option<(f64, f64)> {
-        let tracker = CostTracker::open().ok()?;
-        let since = Self::week_start();
+    fn calculate(
+        ctx: &RenderContext,
+        calendar: &Calendar,
+        weekly_limit: f64,
+    ) -> Option<(f64, f64)> {
+        let tracker = ctx.tracker()?;
+        let since = calendar.start(Period::Week, ctx.now());
         let spent = tracker.total_cost_since(since);
         let pct = if weekly_limit > 0.0 {
             (spent / weekly_limit) * 100.0
@@ -38,15 +28,25 @@ impl Widget for CostWarningWidget {
         "cost-warning"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["weekly_limit", "warn_threshold", "critical_threshold"]
+    }
+
+    fn render(
+        &self,
+        _data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         // Pro-only: gracefully hidden if not Pro
-        if !crate::license::is_pro() {
+        if !ctx.is_pro() {
             return WidgetOutput {
                 text: String::new(),
                 display_width: 0,
                 priority: 75,
                 visible: false,
                 color_hint: None,
+                value: None,
             };
         }
 
@@ -68,7 +68,7 @@ impl Widget for CostWarningWidget {
             .and_then(|v| v.parse().ok())
             .unwrap_or(0.9);
 
-        let (spent, pct) = match Self::calculate(weekly_limit) {
+        let (spent, pct) = match Self::calculate(ctx, ctx.calendar(), weekly_limit) {
             Some(v) => v,
             None => {
                 return WidgetOutput {
@@ -77,6 +77,7 @@ impl Widget for CostWarningWidget {
                     priority: 75,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -91,10 +92,23 @@ impl Widget for CostWarningWidget {
                 priority: 75,
                 visible: false,
                 color_hint: None,
+                value: None,
             };
         }
 
-        let (text, color) = if fraction >= critical_threshold {
+        let (text, color) = if config.compact {
+            let icon = if fraction >= critical_threshold {
+                "\u{1F534}"
+            } else {
+                "\u{26A0}\u{FE0F}"
+            };
+            let color = if fraction >= critical_threshold {
+                "red"
+            } else {
+                "yellow"
+            };
+            (format!("{icon} {:.0}%", pct), color.to_string())
+        } else if fraction >= critical_threshold {
             (
                 format!(
                     "{} {:.0}% of weekly limit (${:.0}/${:.0})",
@@ -118,13 +132,13 @@ impl Widget for CostWarningWidget {
             )
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 75,
             visible: true,
             color_hint: Some(color),
+            value: Some(pct),
         }
     }
 }
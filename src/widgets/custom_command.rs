@@ -1,51 +1,185 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::layout::strip_ansi;
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::process::Command;
-use std::time::{Duration, SystemTime};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+pub(super) const DEFAULT_TIMEOUT_MS: u64 = 1000;
+pub(super) const DEFAULT_CACHE_TTL_SECS: u64 = 10;
 
 pub struct CustomCommandWidget;
 
-fn cache_path(command: &str) -> std::path::PathBuf {
-    let hash: String = command
-        .bytes()
-        .take(16)
-        .map(|b| format!("{:02x}", b))
-        .collect();
+fn cache_path(command: &str, data: &SessionData) -> std::path::PathBuf {
+    let session = data.session_id.as_deref().unwrap_or("");
+    let key = format!("{session}\0{command}");
+    let hash = hex::encode(&Sha256::digest(key.as_bytes())[..8]);
     std::path::PathBuf::from(format!("/tmp/claude-status-cmd-{hash}"))
 }
 
-fn read_cache(path: &std::path::Path) -> Option<String> {
+/// The cached output, if any, and whether it is younger than `ttl`.
+pub(super) fn read_cache(path: &std::path::Path, ttl: Duration) -> Option<(String, bool)> {
     let metadata = fs::metadata(path).ok()?;
-    let modified = metadata.modified().ok()?;
-    if SystemTime::now().duration_since(modified).ok()? > Duration::from_secs(10) {
-        return None;
+    let age = SystemTime::now()
+        .duration_since(metadata.modified().ok()?)
+        .unwrap_or_default();
+    Some((fs::read_to_string(path).ok()?, age <= ttl))
+}
+
+/// Key session fields for scripts that don't want to parse the JSON.
+fn session_env(data: &SessionData) -> Vec<(&'static str, String)> {
+    let mut env = Vec::new();
+    let mut set = |key, value: Option<String>| {
+        if let Some(v) = value {
+            env.push((key, v));
+        }
+    };
+    let model = data.model.as_ref();
+    set("CLAUDE_STATUS_MODEL", model.and_then(|m| m.id.clone()));
+    set(
+        "CLAUDE_STATUS_MODEL_NAME",
+        model.and_then(|m| m.display_name.clone()),
+    );
+    set("CLAUDE_STATUS_SESSION_ID", data.session_id.clone());
+    set("CLAUDE_STATUS_CWD", data.cwd.clone());
+    set(
+        "CLAUDE_STATUS_PROJECT_DIR",
+        data.workspace.as_ref().and_then(|w| w.project_dir.clone()),
+    );
+    set("CLAUDE_STATUS_TRANSCRIPT", data.transcript_path.clone());
+    set("CLAUDE_STATUS_VERSION", data.version.clone());
+    set(
+        "CLAUDE_STATUS_COST_USD",
+        data.cost
+            .as_ref()
+            .and_then(|c| c.total_cost_usd)
+            .map(|c| c.to_string()),
+    );
+    set(
+        "CLAUDE_STATUS_CONTEXT_PERCENT",
+        data.context_window
+            .as_ref()
+            .and_then(|c| c.used_percentage)
+            .map(|p| p.to_string()),
+    );
+    env
+}
+
+pub(super) enum RunResult {
+    Output(String),
+    Failed,
+    TimedOut,
+}
+
+/// Run `cmd` with the session JSON on stdin, killing it after `timeout`.
+/// Its output is the first line it prints.
+fn run_command(cmd: &str, data: &SessionData, timeout: Duration) -> RunResult {
+    let input = data
+        .raw
+        .clone()
+        .unwrap_or_else(|| serde_json::to_string(data).unwrap_or_default());
+
+    let mut command = Command::new("/bin/sh");
+    command.arg("-c").arg(cmd).envs(session_env(data));
+    match run_with_timeout(command, input, timeout) {
+        RunResult::Output(stdout) => match stdout.lines().next().map(str::trim) {
+            Some(line) if !line.is_empty() => RunResult::Output(line.to_string()),
+            _ => RunResult::Failed,
+        },
+        other => other,
     }
-    fs::read_to_string(path).ok()
 }
 
-fn run_command(cmd: &str) -> Option<String> {
-    let child = Command::new("/bin/sh")
-        .arg("-c")
-        .arg(cmd)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::null())
+/// Run `command` with `input` on stdin, killing it after `timeout`. Only a
+/// successful exit has output. Anything it leaves running in the background
+/// is killed along with it once the time is up, so a script that starts a
+/// daemon can't hold the render open through the stdout it inherited.
+pub(super) fn run_with_timeout(
+    mut command: Command,
+    input: String,
+    timeout: Duration,
+) -> RunResult {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = match command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
         .spawn()
-        .ok()?;
+    {
+        Ok(c) => c,
+        Err(_) => return RunResult::Failed,
+    };
 
-    let output = child.wait_with_output().ok()?;
+    // Feed stdin and drain stdout on threads so neither side can block the
+    // wait below; a script that ignores its input is fine.
+    if let Some(mut stdin) = child.stdin.take() {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    let (tx, rx) = mpsc::channel();
+    let stdout = child.stdout.take();
+    std::thread::spawn(move || {
+        let Some(mut stdout) = stdout else {
+            return;
+        };
+        let mut buf = [0u8; 4096];
+        while let Ok(n @ 1..) = stdout.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
 
-    if !output.status.success() {
-        return None;
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                kill_group(&mut child);
+                let _ = child.wait();
+                return RunResult::TimedOut;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(5)),
+            Err(_) => return RunResult::Failed,
+        }
+    };
+    if !status.success() {
+        return RunResult::Failed;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let first_line = stdout.lines().next()?.trim().to_string();
-    if first_line.is_empty() {
-        None
-    } else {
-        Some(first_line)
+    let mut stdout = Vec::new();
+    loop {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(chunk) => stdout.extend(chunk),
+            Err(RecvTimeoutError::Disconnected) => break,
+            // Something it started still holds stdout; keep what it printed
+            Err(RecvTimeoutError::Timeout) => {
+                kill_group(&mut child);
+                break;
+            }
+        }
     }
+    RunResult::Output(String::from_utf8_lossy(&stdout).into_owned())
+}
+
+/// Kill `child` and everything else in its process group.
+fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    // SAFETY: kill(2) takes no pointers; a negative pid names the group
+    // `run_with_timeout` put the child at the head of.
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
 }
 
 impl Widget for CustomCommandWidget {
@@ -53,48 +187,70 @@ impl Widget for CustomCommandWidget {
         "custom-command"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["command", "timeout_ms", "cache_ttl", "ansi"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let hidden = WidgetOutput {
+            text: String::new(),
+            display_width: 0,
+            priority: 40,
+            visible: false,
+            color_hint: None,
+            value: None,
+        };
         let cmd = match config.metadata.get("command") {
             Some(c) if !c.is_empty() => c,
-            _ => {
-                return WidgetOutput {
-                    text: String::new(),
-                    display_width: 0,
-                    priority: 40,
-                    visible: false,
-                    color_hint: None,
-                };
-            }
+            _ => return hidden,
         };
+        let timeout = config
+            .metadata
+            .get("timeout_ms")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_TIMEOUT_MS);
+        let ttl = config
+            .metadata
+            .get("cache_ttl")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECS);
+        let keep_ansi = config
+            .metadata
+            .get("ansi")
+            .map(|v| v == "true")
+            .unwrap_or(false);
 
-        let path = cache_path(cmd);
-        let text = if let Some(cached) = read_cache(&path) {
-            cached
-        } else {
-            match run_command(cmd) {
-                Some(result) => {
+        let path = cache_path(cmd, data);
+        let cached = read_cache(&path, Duration::from_secs(ttl));
+        let text = match cached {
+            Some((text, true)) => text,
+            stale => match run_command(cmd, data, Duration::from_millis(timeout)) {
+                RunResult::Output(result) => {
                     let _ = fs::write(&path, &result);
                     result
                 }
-                None => {
-                    return WidgetOutput {
-                        text: String::new(),
-                        display_width: 0,
-                        priority: 40,
-                        visible: false,
-                        color_hint: None,
-                    };
-                }
-            }
+                // A hung script keeps showing its last good output.
+                RunResult::TimedOut => match stale {
+                    Some((text, _)) => text,
+                    None => return hidden,
+                },
+                RunResult::Failed => return hidden,
+            },
         };
 
-        let display_width = text.len();
+        let text = if keep_ansi { text } else { strip_ansi(&text) };
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 40,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
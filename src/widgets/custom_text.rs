@@ -1,6 +1,6 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
-use unicode_width::UnicodeWidthStr;
 
 pub struct CustomTextWidget;
 
@@ -9,7 +9,16 @@ impl Widget for CustomTextWidget {
         "custom-text"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["text"]
+    }
+
+    fn render(
+        &self,
+        _data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let text = match config.metadata.get("text") {
             Some(t) if !t.is_empty() => t.clone(),
             _ => {
@@ -19,17 +28,18 @@ impl Widget for CustomTextWidget {
                     priority: 30,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
-        let display_width = UnicodeWidthStr::width(text.as_str());
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 30,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::path::Path;
 
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct CwdWidget;
@@ -70,7 +71,16 @@ impl Widget for CwdWidget {
         "cwd"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["full", "fish_style", "segments"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let dir = match get_working_dir(data) {
             Some(d) => d,
             None => {
@@ -80,11 +90,17 @@ impl Widget for CwdWidget {
                     priority: 80,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
-        let text = if config.metadata.get("fish_style").map(|v| v.as_str()) == Some("true") {
+        // Compact mode abbreviates multi-segment paths; basename is already short.
+        let multi_segment =
+            config.metadata.contains_key("full") || config.metadata.contains_key("segments");
+        let text = if config.metadata.get("fish_style").map(|v| v.as_str()) == Some("true")
+            || (config.compact && multi_segment)
+        {
             fish_style(&dir)
         } else if config.metadata.get("full").map(|v| v.as_str()) == Some("true") {
             abbreviate_home(&dir)
@@ -99,13 +115,13 @@ impl Widget for CwdWidget {
                 .unwrap_or_else(|| dir.clone())
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 80,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
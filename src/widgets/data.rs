@@ -1,6 +1,6 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct SessionData {
     pub cwd: Option<String>,
     pub session_id: Option<String>,
@@ -14,26 +14,29 @@ pub struct SessionData {
     pub exceeds_200k_tokens: Option<bool>,
     pub vim: Option<Vim>,
     pub agent: Option<Agent>,
+    /// The JSON this was parsed from, as received on stdin.
+    #[serde(skip)]
+    pub raw: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Model {
     pub id: Option<String>,
     pub display_name: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Workspace {
     pub current_dir: Option<String>,
     pub project_dir: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct OutputStyle {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Cost {
     pub total_cost_usd: Option<f64>,
     pub total_duration_ms: Option<u64>,
@@ -42,7 +45,7 @@ pub struct Cost {
     pub total_lines_removed: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ContextWindow {
     pub total_input_tokens: Option<u64>,
     pub total_output_tokens: Option<u64>,
@@ -52,7 +55,7 @@ pub struct ContextWindow {
     pub current_usage: Option<CurrentUsage>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct CurrentUsage {
     pub input_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
@@ -60,12 +63,12 @@ pub struct CurrentUsage {
     pub cache_read_input_tokens: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Vim {
     pub mode: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Agent {
     pub name: Option<String>,
 }
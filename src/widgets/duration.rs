@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct SessionDurationWidget;
@@ -27,7 +28,16 @@ impl Widget for SessionDurationWidget {
         "session-duration"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["api_ratio"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let cost = match &data.cost {
             Some(c) => c,
             None => {
@@ -37,6 +47,7 @@ impl Widget for SessionDurationWidget {
                     priority: 65,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -50,11 +61,12 @@ impl Widget for SessionDurationWidget {
                     priority: 65,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
-        let text = if config.raw_value {
+        let text = if config.raw_value || config.compact {
             format_duration(duration_ms, true)
         } else if config
             .metadata
@@ -76,13 +88,13 @@ impl Widget for SessionDurationWidget {
             format_duration(duration_ms, false)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 65,
             visible: true,
             color_hint: None,
+            value: Some(duration_ms as f64 / 60_000.0),
         }
     }
 }
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct ExceedsTokensWidget;
@@ -8,17 +9,22 @@ impl Widget for ExceedsTokensWidget {
         "exceeds-tokens"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
         match data.exceeds_200k_tokens {
             Some(true) => {
                 let text = "!200K".to_string();
-                let display_width = text.len();
                 WidgetOutput {
                     text,
-                    display_width,
+                    display_width: 0,
                     priority: 95,
                     visible: true,
                     color_hint: None,
+                    value: None,
                 }
             }
             _ => WidgetOutput {
@@ -27,6 +33,7 @@ impl Widget for ExceedsTokensWidget {
                 priority: 95,
                 visible: false,
                 color_hint: None,
+                value: None,
             },
         }
     }
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct FlexSeparatorWidget;
@@ -8,7 +9,16 @@ impl Widget for FlexSeparatorWidget {
         "flex-separator"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["char"]
+    }
+
+    fn render(
+        &self,
+        _data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let fill_char = config
             .metadata
             .get("char")
@@ -17,13 +27,13 @@ impl Widget for FlexSeparatorWidget {
             .unwrap_or_else(|| " ".to_string());
 
         // Return a marker; the layout engine expands this to fill available width.
-        // display_width of 0 signals the layout engine to calculate the fill.
         WidgetOutput {
             text: fill_char,
             display_width: 0,
             priority: 100,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -1,148 +1,148 @@
-use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
-use std::time::SystemTime;
-
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::git::GitSnapshot;
 
 pub struct GitStatusWidget;
 
-fn cache_path(prefix: &str, dir: &str) -> PathBuf {
-    let hash: String = dir.bytes().take(8).map(|b| format!("{:02x}", b)).collect();
-    PathBuf::from(format!("/tmp/claude-status-{prefix}-{hash}"))
+/// Which optional indicators to show, from the widget metadata.
+struct StatusOptions {
+    upstream: bool,
+    ahead_behind: bool,
+    stash: bool,
+    conflicts: bool,
 }
 
-fn read_cache(path: &PathBuf, max_age_secs: u64) -> Option<String> {
-    let meta = fs::metadata(path).ok()?;
-    let age = SystemTime::now()
-        .duration_since(meta.modified().ok()?)
-        .ok()?;
-    if age.as_secs() <= max_age_secs {
-        fs::read_to_string(path).ok()
-    } else {
-        None
+impl StatusOptions {
+    fn from_config(config: &WidgetConfig) -> Self {
+        let flag = |key: &str| {
+            config
+                .metadata
+                .get(key)
+                .map(|v| v == "true")
+                .unwrap_or(false)
+        };
+        Self {
+            upstream: flag("upstream") && !config.compact,
+            ahead_behind: flag("ahead_behind"),
+            stash: flag("stash"),
+            // Shown unless turned off: unmerged files need attention
+            conflicts: config
+                .metadata
+                .get("conflicts")
+                .is_none_or(|v| v != "false"),
+        }
     }
 }
 
-fn get_working_dir(data: &SessionData) -> Option<String> {
-    data.workspace
-        .as_ref()
-        .and_then(|w| w.current_dir.clone())
-        .or_else(|| data.cwd.clone())
-}
-
-fn format_status(staged: usize, modified: usize, untracked: usize) -> String {
+fn format_status(snap: &GitSnapshot, opts: &StatusOptions) -> String {
     let mut parts = Vec::new();
-    if staged > 0 {
-        parts.push(format!("+{staged}"));
+    if opts.upstream
+        && let Some(upstream) = &snap.upstream
+    {
+        parts.push(upstream.clone());
+    }
+    if opts.conflicts && snap.conflicts > 0 {
+        parts.push(format!("!{}", snap.conflicts));
+    }
+    if snap.staged > 0 {
+        parts.push(format!("+{}", snap.staged));
     }
+    let modified = snap.modified + snap.renamed;
     if modified > 0 {
         parts.push(format!("~{modified}"));
     }
-    if untracked > 0 {
-        parts.push(format!("?{untracked}"));
+    if snap.untracked > 0 {
+        parts.push(format!("?{}", snap.untracked));
+    }
+    if opts.ahead_behind {
+        if snap.ahead > 0 {
+            parts.push(format!("↑{}", snap.ahead));
+        }
+        if snap.behind > 0 {
+            parts.push(format!("↓{}", snap.behind));
+        }
+    }
+    if opts.stash && snap.stash > 0 {
+        parts.push(format!("≡{}", snap.stash));
     }
     parts.join(" ")
 }
 
+/// Color for the most urgent indicator shown: conflicts, then commits not in
+/// sync with upstream, then stashes. Each can be overridden in metadata.
+fn status_color(snap: &GitSnapshot, opts: &StatusOptions, config: &WidgetConfig) -> Option<String> {
+    let color = |key: &str, default: &str| {
+        Some(
+            config
+                .metadata
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| default.to_string()),
+        )
+    };
+    if opts.conflicts && snap.conflicts > 0 {
+        color("conflicts_color", "red")
+    } else if opts.ahead_behind && (snap.ahead > 0 || snap.behind > 0) {
+        color("ahead_behind_color", "yellow")
+    } else if opts.stash && snap.stash > 0 {
+        color("stash_color", "cyan")
+    } else {
+        None
+    }
+}
+
 impl Widget for GitStatusWidget {
     fn name(&self) -> &str {
         "git-status"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
-        let dir = match get_working_dir(data) {
-            Some(d) => d,
-            None => {
-                return WidgetOutput {
-                    text: String::new(),
-                    display_width: 0,
-                    priority: 70,
-                    visible: false,
-                    color_hint: None,
-                };
-            }
-        };
-
-        let cache = cache_path("git-status", &dir);
-
-        if let Some(cached) = read_cache(&cache, 5) {
-            let text = cached.trim().to_string();
-            if text.is_empty() {
-                return WidgetOutput {
-                    text: String::new(),
-                    display_width: 0,
-                    priority: 70,
-                    visible: true,
-                    color_hint: None,
-                };
-            }
-            let display_width = text.len();
-            return WidgetOutput {
-                text,
-                display_width,
-                priority: 70,
-                visible: true,
-                color_hint: None,
-            };
-        }
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &[
+            "upstream",
+            "ahead_behind",
+            "stash",
+            "conflicts",
+            "conflicts_color",
+            "ahead_behind_color",
+            "stash_color",
+        ]
+    }
 
-        let output = match Command::new("git")
-            .args(["status", "--porcelain"])
-            .current_dir(&dir)
-            .output()
-        {
-            Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
-            _ => {
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let snap = match ctx.git().snapshot(data) {
+            Some(s) => s,
+            None => {
                 return WidgetOutput {
                     text: String::new(),
                     display_width: 0,
                     priority: 70,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
-        let mut staged = 0usize;
-        let mut modified = 0usize;
-        let mut untracked = 0usize;
-
-        for line in output.lines() {
-            let bytes = line.as_bytes();
-            if bytes.len() < 2 {
-                continue;
-            }
-            let index = bytes[0];
-            let worktree = bytes[1];
-
-            if index == b'?' && worktree == b'?' {
-                untracked += 1;
-            } else {
-                // Index column: staged changes
-                if matches!(index, b'A' | b'M' | b'D' | b'R') {
-                    staged += 1;
-                }
-                // Working tree column: modified/deleted
-                if matches!(worktree, b'M' | b'D') {
-                    modified += 1;
-                }
-            }
-        }
-
-        let text = format_status(staged, modified, untracked);
-
-        // Write cache
-        let _ = fs::write(&cache, &text);
+        let opts = StatusOptions::from_config(config);
+        let text = format_status(snap, &opts);
+        let color_hint = status_color(snap, &opts, config);
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 70,
             visible: true,
-            color_hint: None,
+            color_hint,
+            value: Some(
+                (snap.staged + snap.modified + snap.renamed + snap.untracked + snap.conflicts)
+                    as f64,
+            ),
         }
     }
 }
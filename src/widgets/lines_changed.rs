@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct LinesChangedWidget;
@@ -8,7 +9,12 @@ impl Widget for LinesChangedWidget {
         "lines-changed"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let added = data
             .cost
             .as_ref()
@@ -27,6 +33,7 @@ impl Widget for LinesChangedWidget {
                 priority: 40,
                 visible: false,
                 color_hint: None,
+                value: None,
             };
         }
 
@@ -36,13 +43,13 @@ impl Widget for LinesChangedWidget {
             format!("+{added} -{removed}")
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 40,
             visible: true,
             color_hint: None,
+            value: Some((added + removed) as f64),
         }
     }
 }
@@ -1,5 +1,6 @@
 pub mod data;
 mod registry;
+mod render_context;
 mod traits;
 
 // Widget implementations
@@ -23,13 +24,20 @@ mod lines_changed;
 mod model;
 mod model_suggest;
 mod output_style;
+mod plugin;
+mod project_budget;
 mod separator;
 mod session_id;
+mod template;
 mod terminal_width;
 mod tokens;
+mod transcript;
 mod version;
 mod vim_mode;
+mod zone;
 
 pub use data::*;
+pub use plugin::PluginWidget;
 pub use registry::WidgetRegistry;
+pub use render_context::RenderContext;
 pub use traits::{Widget, WidgetConfig, WidgetOutput};
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct ModelWidget;
@@ -8,7 +9,12 @@ impl Widget for ModelWidget {
         "model"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let model = match &data.model {
             Some(m) => m,
             None => {
@@ -18,6 +24,7 @@ impl Widget for ModelWidget {
                     priority: 90,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -32,13 +39,13 @@ impl Widget for ModelWidget {
                 .unwrap_or_default()
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 90,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -1,5 +1,7 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::pricing::Pricing;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Complexity {
@@ -56,38 +58,27 @@ impl ModelSuggestWidget {
         }
     }
 
-    /// Suggest a cheaper model if appropriate.
+    /// Suggest a cheaper model if appropriate, with the dollars the session
+    /// so far would have saved on it.
     fn suggest(
+        data: &SessionData,
+        pricing: &Pricing,
         current_tier: &str,
         complexity: Complexity,
         min_savings: f64,
     ) -> Option<(String, f64)> {
-        match (current_tier, complexity) {
-            ("opus", Complexity::Simple) => {
-                let savings = 0.32;
-                if savings >= min_savings {
-                    Some(("Sonnet".into(), savings))
-                } else {
-                    None
-                }
-            }
-            ("opus", Complexity::Medium) => {
-                let savings = 0.32;
-                if savings >= min_savings {
-                    Some(("Sonnet".into(), savings))
-                } else {
-                    None
-                }
-            }
-            ("sonnet", Complexity::Simple) => {
-                let savings = 0.09;
-                if savings >= min_savings {
-                    Some(("Haiku".into(), savings))
-                } else {
-                    None
-                }
-            }
-            _ => None,
+        let (name, target) = match (current_tier, complexity) {
+            ("opus", Complexity::Simple | Complexity::Medium) => ("Sonnet", "sonnet"),
+            ("sonnet", Complexity::Simple) => ("Haiku", "haiku"),
+            _ => return None,
+        };
+
+        let spent = data.cost.as_ref().and_then(|c| c.total_cost_usd)?;
+        let savings = spent - pricing.cost_on(data, target)?;
+        if savings >= min_savings {
+            Some((name.into(), savings))
+        } else {
+            None
         }
     }
 }
@@ -97,15 +88,25 @@ impl Widget for ModelSuggestWidget {
         "model-suggest"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["min_savings"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         // Pro-only: gracefully hidden if not Pro
-        if !crate::license::is_pro() {
+        if !ctx.is_pro() {
             return WidgetOutput {
                 text: String::new(),
                 display_width: 0,
                 priority: 60,
                 visible: false,
                 color_hint: None,
+                value: None,
             };
         }
 
@@ -118,6 +119,7 @@ impl Widget for ModelSuggestWidget {
                     priority: 60,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -131,6 +133,7 @@ impl Widget for ModelSuggestWidget {
                     priority: 60,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -144,7 +147,7 @@ impl Widget for ModelSuggestWidget {
         let complexity = Self::analyze_complexity(data);
 
         let (suggested_model, savings) =
-            match Self::suggest(current_tier, complexity, min_savings) {
+            match Self::suggest(data, ctx.pricing(), current_tier, complexity, min_savings) {
                 Some(s) => s,
                 None => {
                     return WidgetOutput {
@@ -153,6 +156,7 @@ impl Widget for ModelSuggestWidget {
                         priority: 60,
                         visible: false,
                         color_hint: None,
+                        value: None,
                     };
                 }
             };
@@ -160,19 +164,16 @@ impl Widget for ModelSuggestWidget {
         let text = if config.raw_value {
             format!("{}:{:.2}", suggested_model, savings)
         } else {
-            format!(
-                "\u{1F4A1} Try {} -> Save ${:.2}",
-                suggested_model, savings
-            )
+            format!("\u{1F4A1} Try {} -> Save ${:.2}", suggested_model, savings)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 60,
             visible: true,
             color_hint: Some("cyan".into()),
+            value: Some(savings),
         }
     }
 }
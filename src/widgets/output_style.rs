@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct OutputStyleWidget;
@@ -8,7 +9,12 @@ impl Widget for OutputStyleWidget {
         "output-style"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
         let style = match &data.output_style {
             Some(s) => s,
             None => {
@@ -18,6 +24,7 @@ impl Widget for OutputStyleWidget {
                     priority: 30,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -31,17 +38,18 @@ impl Widget for OutputStyleWidget {
                     priority: 30,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
-        let display_width = name.len();
         WidgetOutput {
             text: name,
-            display_width,
+            display_width: 0,
             priority: 30,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::custom_command::{
+    DEFAULT_CACHE_TTL_SECS, DEFAULT_TIMEOUT_MS, RunResult, read_cache, run_with_timeout,
+};
+use super::data::SessionData;
+use super::render_context::RenderContext;
+use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::config::Config;
+
+/// A widget provided by an executable in the plugins directory.
+///
+/// Run with `--describe`, the plugin prints what it provides:
+///
+/// ```json
+/// {"name": "ticket", "metadata": ["project"], "timeout_ms": 500, "cache_ttl": 30}
+/// ```
+///
+/// To render, it gets `{"session": ..., "metadata": {...}, "compact": false}`
+/// on stdin, with the status line input as `session`, and replies with
+/// `{"text": "...", "priority": 50, "visible": true, "color_hint": "red"}`.
+/// Only `text` is required. Replies are cached like `custom-command` output,
+/// and `timeout_ms` and `cache_ttl` can be overridden in the widget's metadata.
+pub struct PluginWidget {
+    path: PathBuf,
+    description: Description,
+}
+
+#[derive(Debug, Deserialize)]
+struct Description {
+    name: String,
+    #[serde(default)]
+    metadata: Vec<String>,
+    timeout_ms: Option<u64>,
+    cache_ttl: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    #[serde(default)]
+    text: String,
+    #[serde(default = "default_priority")]
+    priority: u8,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    color_hint: Option<String>,
+    value: Option<f64>,
+}
+
+fn default_priority() -> u8 {
+    50
+}
+fn default_visible() -> bool {
+    true
+}
+
+impl PluginWidget {
+    /// Directory plugins are loaded from.
+    pub fn dir() -> Option<PathBuf> {
+        Config::default_path()?.parent().map(|d| d.join("plugins"))
+    }
+
+    /// Every executable in `dir` that describes itself, sorted by path.
+    pub fn load_dir(dir: &Path) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| is_executable(p))
+            .collect();
+        paths.sort();
+        paths.into_iter().filter_map(|p| Self::load(&p)).collect()
+    }
+
+    /// Ask the executable at `path` to describe itself. The description is
+    /// cached until the file changes, so plugins aren't run on every render.
+    pub fn load(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let modified = meta
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let key = format!(
+            "{}\0{}\0{}",
+            path.display(),
+            modified.as_nanos(),
+            meta.len()
+        );
+        let cache = cache_path("describe", &key);
+
+        let description = match fs::read_to_string(&cache) {
+            Ok(text) => text,
+            Err(_) => {
+                let mut command = Command::new(path);
+                command.arg("--describe");
+                let timeout = Duration::from_millis(DEFAULT_TIMEOUT_MS);
+                // A failure is cached too, so a broken plugin doesn't slow
+                // down every render until it is fixed.
+                let text = match run_with_timeout(command, String::new(), timeout) {
+                    RunResult::Output(text) => text,
+                    RunResult::Failed | RunResult::TimedOut => String::new(),
+                };
+                let _ = fs::write(&cache, &text);
+                text
+            }
+        };
+        let description: Description = serde_json::from_str(&description).ok()?;
+        if description.name.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path: path.to_path_buf(),
+            description,
+        })
+    }
+}
+
+impl Widget for PluginWidget {
+    fn name(&self) -> &str {
+        &self.description.name
+    }
+
+    fn accepts_metadata(&self, key: &str) -> bool {
+        matches!(key, "timeout_ms" | "cache_ttl")
+            || self.description.metadata.iter().any(|k| k == key)
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let hidden = WidgetOutput {
+            text: String::new(),
+            display_width: 0,
+            priority: default_priority(),
+            visible: false,
+            color_hint: None,
+            value: None,
+        };
+        let setting = |key, default| {
+            config
+                .metadata
+                .get(key)
+                .and_then(|v| v.parse().ok())
+                .or(default)
+        };
+        let timeout = setting("timeout_ms", self.description.timeout_ms);
+        let ttl = setting("cache_ttl", self.description.cache_ttl);
+
+        let session: serde_json::Value = data
+            .raw
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_else(|| serde_json::to_value(data).unwrap_or_default());
+        // Sorted, so the cache key doesn't depend on map order
+        let metadata: BTreeMap<_, _> = config.metadata.iter().collect();
+        let input = serde_json::json!({
+            "session": session,
+            "metadata": metadata,
+            "compact": config.compact,
+        })
+        .to_string();
+
+        let key = format!(
+            "{}\0{}\0{}\0{}",
+            data.session_id.as_deref().unwrap_or(""),
+            self.path.display(),
+            serde_json::to_string(&metadata).unwrap_or_default(),
+            config.compact,
+        );
+        let path = cache_path("out", &key);
+        let ttl = Duration::from_secs(ttl.unwrap_or(DEFAULT_CACHE_TTL_SECS));
+        let cached = read_cache(&path, ttl);
+        let reply = match cached {
+            Some((text, true)) => text,
+            stale => {
+                let timeout = Duration::from_millis(timeout.unwrap_or(DEFAULT_TIMEOUT_MS));
+                match run_with_timeout(Command::new(&self.path), input, timeout) {
+                    RunResult::Output(reply) => {
+                        let _ = fs::write(&path, &reply);
+                        reply
+                    }
+                    // A hung plugin keeps showing its last good reply.
+                    RunResult::TimedOut => match stale {
+                        Some((text, _)) => text,
+                        None => return hidden,
+                    },
+                    RunResult::Failed => return hidden,
+                }
+            }
+        };
+
+        let Ok(reply) = serde_json::from_str::<Reply>(&reply) else {
+            return hidden;
+        };
+        WidgetOutput {
+            visible: reply.visible && !reply.text.is_empty(),
+            text: reply.text,
+            display_width: 0,
+            priority: reply.priority,
+            color_hint: reply.color_hint,
+            value: reply.value,
+        }
+    }
+}
+
+fn cache_path(kind: &str, key: &str) -> PathBuf {
+    let hash = hex::encode(&Sha256::digest(key.as_bytes())[..8]);
+    PathBuf::from(format!("/tmp/claude-status-plugin-{kind}-{hash}"))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use super::data::SessionData;
+use super::render_context::RenderContext;
+use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::calendar::Period;
+
+/// Budget left for the current project under the `[budgets]` config
+/// section. With several limits set, shows the one closest to running out.
+pub struct ProjectBudgetWidget;
+
+impl ProjectBudgetWidget {
+    /// The project's tightest limit as `(period, limit, spent)`.
+    fn tightest(
+        data: &SessionData,
+        ctx: &RenderContext,
+        project: &str,
+    ) -> Option<(Period, f64, f64)> {
+        let budget = ctx.budgets().for_project(project)?;
+        let tracker = ctx.tracker()?;
+
+        // This render's spend is recorded after it is drawn
+        let session_cost = data
+            .cost
+            .as_ref()
+            .and_then(|c| c.total_cost_usd)
+            .unwrap_or(0.0);
+        let recorded = data
+            .session_id
+            .as_deref()
+            .and_then(|id| tracker.get_session(id))
+            .map_or(0.0, |s| s.total_cost);
+        let unrecorded = (session_cost - recorded).max(0.0);
+
+        budget
+            .limits()
+            .map(|(period, limit)| {
+                let since = ctx.calendar().start(period, ctx.now());
+                let spent = tracker.project_cost_since(project, since) + unrecorded;
+                (period, limit, spent)
+            })
+            .max_by(|a, b| (a.2 / a.1).total_cmp(&(b.2 / b.1)))
+    }
+}
+
+impl Widget for ProjectBudgetWidget {
+    fn name(&self) -> &str {
+        "project-budget"
+    }
+
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["warn_threshold", "critical_threshold"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let hidden = WidgetOutput {
+            text: String::new(),
+            display_width: 0,
+            priority: 70,
+            visible: false,
+            color_hint: None,
+            value: None,
+        };
+        // Pro-only: gracefully hidden if not Pro
+        if !ctx.is_pro() {
+            return hidden;
+        }
+
+        let Some(project) = data
+            .workspace
+            .as_ref()
+            .and_then(|w| w.project_dir.clone())
+            .or_else(|| data.cwd.clone())
+        else {
+            return hidden;
+        };
+        let Some((period, limit, spent)) = Self::tightest(data, ctx, &project) else {
+            return hidden;
+        };
+
+        let threshold = |key: &str, default: f64| {
+            config
+                .metadata
+                .get(key)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let warn_threshold = threshold("warn_threshold", 0.7);
+        let critical_threshold = threshold("critical_threshold", 0.9);
+
+        let fraction = if limit > 0.0 { spent / limit } else { 1.0 };
+        let color = if fraction >= critical_threshold {
+            Some("red".to_string())
+        } else if fraction >= warn_threshold {
+            Some("yellow".to_string())
+        } else {
+            None
+        };
+
+        let remaining = limit - spent;
+        let amount = if remaining >= 0.0 {
+            format!("${:.2} left", remaining)
+        } else {
+            format!("${:.2} over", -remaining)
+        };
+        let text = if config.compact {
+            amount
+        } else {
+            let name = Path::new(&project)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or(project);
+            format!("{}: {} {}", name, amount, period.label())
+        };
+
+        WidgetOutput {
+            text,
+            display_width: 0,
+            priority: 70,
+            visible: true,
+            color_hint: color,
+            value: Some(fraction * 100.0),
+        }
+    }
+}
@@ -1,40 +1,84 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
 use super::data::SessionData;
+use super::plugin::PluginWidget;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::layout::display_width;
 
+/// Cheap to clone; clones share the registered widgets.
+#[derive(Clone)]
 pub struct WidgetRegistry {
-    widgets: HashMap<String, Box<dyn Widget>>,
+    widgets: Arc<HashMap<String, Arc<dyn Widget>>>,
 }
 
 impl Default for WidgetRegistry {
     fn default() -> Self {
-        Self::new()
+        Self::builtin()
     }
 }
 
 impl WidgetRegistry {
-    pub fn new() -> Self {
+    /// Only the widgets that ship with the crate. The user's plugins are
+    /// added with `register_plugins`.
+    pub fn builtin() -> Self {
         let mut registry = Self {
-            widgets: HashMap::new(),
+            widgets: Arc::default(),
         };
         registry.register_defaults();
         registry
     }
 
     pub fn register(&mut self, widget: Box<dyn Widget>) {
-        self.widgets.insert(widget.name().to_string(), widget);
+        Arc::make_mut(&mut self.widgets).insert(widget.name().to_string(), Arc::from(widget));
     }
 
+    /// Register the plugins in `dir`. Plugins can't replace built-in
+    /// widgets or each other; the first one with a name wins.
+    pub fn register_plugins(&mut self, dir: &Path) {
+        for plugin in PluginWidget::load_dir(dir) {
+            if !self.contains(plugin.name()) {
+                self.register(Box::new(plugin));
+            }
+        }
+    }
+
+    pub fn contains(&self, widget_type: &str) -> bool {
+        self.widgets.contains_key(widget_type)
+    }
+
+    /// Names of every registered widget type, in no particular order.
+    pub fn widget_types(&self) -> impl Iterator<Item = &str> {
+        self.widgets.keys().map(String::as_str)
+    }
+
+    /// Whether a widget type reads a metadata key, or None if the type is
+    /// unknown.
+    pub fn accepts_metadata(&self, widget_type: &str, key: &str) -> Option<bool> {
+        self.widgets
+            .get(widget_type)
+            .map(|w| w.accepts_metadata(key))
+    }
+
+    /// Render a widget and measure its text. The flex separator is left at
+    /// zero width; the layout engine sizes it to fill the line.
     pub fn render(
         &self,
         widget_type: &str,
         data: &SessionData,
+        ctx: &RenderContext,
         config: &WidgetConfig,
     ) -> Option<WidgetOutput> {
-        self.widgets
-            .get(widget_type)
-            .map(|w| w.render(data, config))
+        let widget = self.widgets.get(widget_type)?;
+        // Widgets that render others, like `template`, use this registry
+        ctx.attach_registry(self);
+        let mut output = widget.render(data, ctx, config);
+        if output.visible && widget_type != "flex-separator" {
+            output.display_width = display_width(&output.text);
+        }
+        Some(output)
     }
 
     fn register_defaults(&mut self) {
@@ -46,6 +90,8 @@ impl WidgetRegistry {
         self.register(Box::new(super::tokens::TokenCachedWidget));
         self.register(Box::new(super::tokens::TokenTotalWidget));
         self.register(Box::new(super::cost::SessionCostWidget));
+        self.register(Box::new(super::cost::TurnCostWidget));
+        self.register(Box::new(super::cost::CostCompareWidget));
         self.register(Box::new(super::duration::SessionDurationWidget));
         self.register(Box::new(super::block_timer::BlockTimerWidget));
         self.register(Box::new(super::git_branch::GitBranchWidget));
@@ -62,13 +108,20 @@ impl WidgetRegistry {
         self.register(Box::new(super::api_duration::ApiDurationWidget));
         self.register(Box::new(super::custom_command::CustomCommandWidget));
         self.register(Box::new(super::custom_text::CustomTextWidget));
+        self.register(Box::new(super::template::TemplateWidget));
         self.register(Box::new(super::separator::SeparatorWidget));
         self.register(Box::new(super::terminal_width::TerminalWidthWidget));
         self.register(Box::new(super::flex_separator::FlexSeparatorWidget));
+        self.register(Box::new(super::zone::ZoneWidget));
+        self.register(Box::new(super::transcript::LastToolWidget));
+        self.register(Box::new(super::transcript::ToolCountWidget));
+        self.register(Box::new(super::transcript::LastPromptWidget));
+        self.register(Box::new(super::transcript::IdleTimeWidget));
 
         // Pro widgets (gracefully hidden when not licensed)
         self.register(Box::new(super::burn_rate::BurnRateWidget));
         self.register(Box::new(super::cost_warning::CostWarningWidget));
         self.register(Box::new(super::model_suggest::ModelSuggestWidget));
+        self.register(Box::new(super::project_budget::ProjectBudgetWidget));
     }
 }
@@ -0,0 +1,175 @@
+use std::cell::OnceCell;
+
+use chrono::{DateTime, Utc};
+
+use crate::budget::Budgets;
+use crate::calendar::Calendar;
+use crate::config::Config;
+use crate::git::{GitContext, GitSnapshot};
+use crate::license::LicenseInfo;
+use crate::pricing::Pricing;
+use crate::storage::CostTracker;
+use crate::transcript::{self, TranscriptState};
+
+use super::data::SessionData;
+use super::registry::WidgetRegistry;
+
+/// State shared by every widget during one render. Each part is loaded on
+/// first use and at most once, so a line of Pro widgets checks the license
+/// once and shares one database connection, and the transcript widgets share
+/// one read of the transcript. Prices, budgets and the calendar come from
+/// the config the layout engine renders with. The `with_*` methods supply a
+/// part up front instead, e.g. a fixed clock or a fake license in tests.
+pub struct RenderContext {
+    now: DateTime<Utc>,
+    git: GitContext,
+    license: OnceCell<Option<LicenseInfo>>,
+    tracker: OnceCell<Option<CostTracker>>,
+    transcript: OnceCell<Option<TranscriptState>>,
+    terminal_width: OnceCell<Option<u16>>,
+    registry: OnceCell<WidgetRegistry>,
+    pricing: OnceCell<Pricing>,
+    budgets: OnceCell<Budgets>,
+    calendar: OnceCell<Calendar>,
+}
+
+impl Default for RenderContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderContext {
+    pub fn new() -> Self {
+        Self {
+            now: Utc::now(),
+            git: GitContext::default(),
+            license: OnceCell::new(),
+            tracker: OnceCell::new(),
+            transcript: OnceCell::new(),
+            terminal_width: OnceCell::new(),
+            registry: OnceCell::new(),
+            pricing: OnceCell::new(),
+            budgets: OnceCell::new(),
+            calendar: OnceCell::new(),
+        }
+    }
+
+    pub fn with_now(mut self, now: DateTime<Utc>) -> Self {
+        self.now = now;
+        self
+    }
+
+    /// Use this git state instead of running `git status`; None is outside
+    /// a repository.
+    pub fn with_git(mut self, snapshot: Option<GitSnapshot>) -> Self {
+        self.git = GitContext::from_snapshot(snapshot);
+        self
+    }
+
+    /// Use this license instead of the stored key; None is unlicensed.
+    pub fn with_license(self, license: Option<LicenseInfo>) -> Self {
+        let _ = self.license.set(license);
+        self
+    }
+
+    /// Use this cost history instead of the database at the default path.
+    pub fn with_tracker(self, tracker: CostTracker) -> Self {
+        let _ = self.tracker.set(Some(tracker));
+        self
+    }
+
+    pub fn with_terminal_width(self, width: u16) -> Self {
+        let _ = self.terminal_width.set(Some(width));
+        self
+    }
+
+    pub fn with_pricing(self, pricing: Pricing) -> Self {
+        let _ = self.pricing.set(pricing);
+        self
+    }
+
+    pub fn with_budgets(self, budgets: Budgets) -> Self {
+        let _ = self.budgets.set(budgets);
+        self
+    }
+
+    pub fn with_calendar(self, calendar: Calendar) -> Self {
+        let _ = self.calendar.set(calendar);
+        self
+    }
+
+    /// Take prices, budgets and the calendar from `config`, where they
+    /// weren't supplied up front. Called by the layout engine.
+    pub(crate) fn attach_config(&self, config: &Config) {
+        self.pricing.get_or_init(|| Pricing::new(&config.pricing));
+        self.budgets.get_or_init(|| Budgets::new(&config.budgets));
+        self.calendar.get_or_init(|| config.calendar);
+    }
+
+    /// The time of this render.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+
+    pub fn git(&self) -> &GitContext {
+        &self.git
+    }
+
+    /// The valid Pro license, if any.
+    pub fn license(&self) -> Option<&LicenseInfo> {
+        self.license.get_or_init(crate::license::check_pro).as_ref()
+    }
+
+    pub fn is_pro(&self) -> bool {
+        self.license().is_some()
+    }
+
+    /// The cost history database; None when it can't be opened.
+    pub fn tracker(&self) -> Option<&CostTracker> {
+        self.tracker
+            .get_or_init(|| CostTracker::open().ok())
+            .as_ref()
+    }
+
+    /// Model prices, including any `[pricing]` overrides from the config.
+    pub fn pricing(&self) -> &Pricing {
+        self.pricing.get_or_init(Pricing::default)
+    }
+
+    /// Project budgets from the `[budgets]` config section.
+    pub fn budgets(&self) -> &Budgets {
+        self.budgets.get_or_init(Budgets::default)
+    }
+
+    /// Where days, weeks and months begin, from the `[calendar]` section.
+    pub fn calendar(&self) -> &Calendar {
+        self.calendar.get_or_init(Calendar::default)
+    }
+
+    /// The registry rendering this context's widgets, for widgets that
+    /// include others. Set by the first `WidgetRegistry::render`.
+    pub fn registry(&self) -> Option<&WidgetRegistry> {
+        self.registry.get()
+    }
+
+    pub(super) fn attach_registry(&self, registry: &WidgetRegistry) {
+        if self.registry.get().is_none() {
+            let _ = self.registry.set(registry.clone());
+        }
+    }
+
+    /// The session transcript, read up to date; None without one.
+    pub fn transcript(&self, data: &SessionData) -> Option<&TranscriptState> {
+        self.transcript
+            .get_or_init(|| transcript::load(data))
+            .as_ref()
+    }
+
+    /// Columns of the terminal; None when there is no terminal to ask.
+    pub fn terminal_width(&self) -> Option<u16> {
+        *self
+            .terminal_width
+            .get_or_init(|| crossterm::terminal::size().ok().map(|(w, _)| w))
+    }
+}
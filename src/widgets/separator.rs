@@ -1,6 +1,6 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
-use unicode_width::UnicodeWidthStr;
 
 pub struct SeparatorWidget;
 
@@ -9,7 +9,16 @@ impl Widget for SeparatorWidget {
         "separator"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["char"]
+    }
+
+    fn render(
+        &self,
+        _data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let text = config
             .metadata
             .get("char")
@@ -17,13 +26,13 @@ impl Widget for SeparatorWidget {
             .cloned()
             .unwrap_or_else(|| "|".to_string());
 
-        let display_width = UnicodeWidthStr::width(text.as_str());
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 100,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
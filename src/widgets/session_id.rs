@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct SessionIdWidget;
@@ -8,7 +9,12 @@ impl Widget for SessionIdWidget {
         "session-id"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
         let sid = match &data.session_id {
             Some(s) => s,
             None => {
@@ -18,19 +24,20 @@ impl Widget for SessionIdWidget {
                     priority: 20,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
         let text: String = sid.chars().take(8).collect();
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 20,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -0,0 +1,190 @@
+use serde_json::Value;
+
+use super::data::SessionData;
+use super::render_context::RenderContext;
+use super::traits::{Widget, WidgetConfig, WidgetOutput};
+use crate::layout::condition::Facts;
+
+/// Interpolates session fields and other widgets into a format string:
+///
+/// - `{model.display_name}`: a field of the status line input (or `git.*`)
+/// - `{cost.total_cost_usd:.3}`: with a format spec, `[<>^][width][.precision]`
+/// - `{vim.mode|INSERT}`: with a default for missing or empty values
+/// - `{widget:git-branch}`: another widget's text
+/// - `[ ({widget:git-status})]`: a section, dropped when any field in it is missing
+///
+/// `{{`, `}}`, `[[` and `]]` are literal braces and brackets.
+pub struct TemplateWidget;
+
+impl Widget for TemplateWidget {
+    fn name(&self) -> &str {
+        "template"
+    }
+
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["template"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let template = config
+            .metadata
+            .get("template")
+            .map(|s| s.as_str())
+            .unwrap_or("");
+        let expander = Expander {
+            data,
+            ctx,
+            config,
+            facts: Facts::new(data, ctx.git()),
+        };
+        let chars: Vec<char> = template.chars().collect();
+        let mut pos = 0;
+        let (text, _) = expander.expand(&chars, &mut pos, false);
+
+        if text.trim().is_empty() {
+            return WidgetOutput {
+                text: String::new(),
+                display_width: 0,
+                priority: 50,
+                visible: false,
+                color_hint: None,
+                value: None,
+            };
+        }
+
+        WidgetOutput {
+            text,
+            display_width: 0,
+            priority: 50,
+            visible: true,
+            color_hint: None,
+            value: None,
+        }
+    }
+}
+
+struct Expander<'a> {
+    data: &'a SessionData,
+    ctx: &'a RenderContext,
+    config: &'a WidgetConfig,
+    facts: Facts<'a>,
+}
+
+impl Expander<'_> {
+    /// Expand until the end of input, or the closing `]` of a section.
+    /// Returns the text and whether every placeholder in it had a value.
+    fn expand(&self, chars: &[char], pos: &mut usize, in_section: bool) -> (String, bool) {
+        let mut out = String::new();
+        let mut complete = true;
+
+        while *pos < chars.len() {
+            let c = chars[*pos];
+            let next = chars.get(*pos + 1).copied();
+            *pos += 1;
+            match c {
+                '{' | '}' | '[' | ']' if next == Some(c) => {
+                    out.push(c);
+                    *pos += 1;
+                }
+                '{' => {
+                    let end = chars[*pos..].iter().position(|&ch| ch == '}');
+                    let Some(end) = end else {
+                        // Unclosed placeholder: keep it as text
+                        out.extend(&chars[*pos - 1..]);
+                        *pos = chars.len();
+                        break;
+                    };
+                    let spec: String = chars[*pos..*pos + end].iter().collect();
+                    *pos += end + 1;
+                    match self.placeholder(&spec) {
+                        Some(text) => out.push_str(&text),
+                        None => complete = false,
+                    }
+                }
+                '[' => {
+                    let (section, section_complete) = self.expand(chars, pos, true);
+                    if section_complete {
+                        out.push_str(&section);
+                    }
+                }
+                ']' if in_section => return (out, complete),
+                c => out.push(c),
+            }
+        }
+
+        (out, complete)
+    }
+
+    /// `path[:spec][|default]` or `widget:name[|default]`.
+    fn placeholder(&self, spec: &str) -> Option<String> {
+        let (body, default) = match spec.split_once('|') {
+            Some((body, default)) => (body.trim(), Some(default)),
+            None => (spec.trim(), None),
+        };
+
+        let text = if let Some(name) = body.strip_prefix("widget:") {
+            self.widget_text(name.trim())
+        } else {
+            let (path, format) = body.split_once(':').unwrap_or((body, ""));
+            format_value(&self.facts.lookup(path.trim()), format)
+        };
+
+        text.filter(|t| !t.is_empty())
+            .or_else(|| default.map(String::from))
+    }
+
+    fn widget_text(&self, name: &str) -> Option<String> {
+        // A template can't include itself.
+        if name == "template" {
+            return None;
+        }
+        let widget_config = WidgetConfig {
+            widget_type: name.to_string(),
+            compact: self.config.compact,
+            ..Default::default()
+        };
+        let output = self
+            .ctx
+            .registry()?
+            .render(name, self.data, self.ctx, &widget_config)?;
+        output.visible.then_some(output.text)
+    }
+}
+
+/// Format a field per `[<>^][width][.precision]`. Precision rounds numbers
+/// and truncates strings. Null, objects and arrays have no text.
+fn format_value(value: &Value, format: &str) -> Option<String> {
+    let (align, rest) = match format.chars().next() {
+        Some(a @ ('<' | '>' | '^')) => (a, &format[1..]),
+        _ => ('<', format),
+    };
+    let (width, precision) = match rest.split_once('.') {
+        Some((w, p)) => (w, p.parse::<usize>().ok()),
+        None => (rest, None),
+    };
+    let width: usize = width.parse().unwrap_or(0);
+
+    let text = match value {
+        Value::String(s) => match precision {
+            Some(p) => s.chars().take(p).collect(),
+            None => s.clone(),
+        },
+        Value::Number(n) => match (precision, n.as_f64()) {
+            (Some(p), Some(f)) => format!("{f:.p$}"),
+            _ => n.to_string(),
+        },
+        Value::Bool(b) => b.to_string(),
+        Value::Null | Value::Array(_) | Value::Object(_) => return None,
+    };
+
+    Some(match align {
+        '>' => format!("{text:>width$}"),
+        '^' => format!("{text:^width$}"),
+        _ => format!("{text:<width$}"),
+    })
+}
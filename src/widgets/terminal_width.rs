@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct TerminalWidthWidget;
@@ -8,8 +9,13 @@ impl Widget for TerminalWidthWidget {
         "terminal-width"
     }
 
-    fn render(&self, _data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
-        let cols = crossterm::terminal::size().map(|(w, _)| w).unwrap_or(80);
+    fn render(
+        &self,
+        _data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let cols = ctx.terminal_width().unwrap_or(80);
 
         let text = if config.raw_value {
             format!("{}", cols)
@@ -17,13 +23,13 @@ impl Widget for TerminalWidthWidget {
             format!("{} cols", cols)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 20,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -1,7 +1,8 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
-fn format_tokens(n: u64, compact: bool) -> String {
+pub(super) fn format_tokens(n: u64, compact: bool) -> String {
     if compact {
         if n >= 1_000_000 {
             format!("{:.1}M", n as f64 / 1_000_000.0)
@@ -30,7 +31,12 @@ impl Widget for TokenInputWidget {
         "tokens-input"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let usage = match data
             .context_window
             .as_ref()
@@ -44,6 +50,7 @@ impl Widget for TokenInputWidget {
                     priority: 55,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -52,16 +59,16 @@ impl Widget for TokenInputWidget {
         let text = if config.raw_value {
             format_tokens(val, true)
         } else {
-            format!("In: {}", format_tokens(val, false))
+            format!("In: {}", format_tokens(val, config.compact))
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 55,
             visible: true,
             color_hint: None,
+            value: Some(val as f64),
         }
     }
 }
@@ -73,7 +80,12 @@ impl Widget for TokenOutputWidget {
         "tokens-output"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let usage = match data
             .context_window
             .as_ref()
@@ -87,6 +99,7 @@ impl Widget for TokenOutputWidget {
                     priority: 53,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -95,16 +108,16 @@ impl Widget for TokenOutputWidget {
         let text = if config.raw_value {
             format_tokens(val, true)
         } else {
-            format!("Out: {}", format_tokens(val, false))
+            format!("Out: {}", format_tokens(val, config.compact))
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 53,
             visible: true,
             color_hint: None,
+            value: Some(val as f64),
         }
     }
 }
@@ -116,7 +129,12 @@ impl Widget for TokenCachedWidget {
         "tokens-cached"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let usage = match data
             .context_window
             .as_ref()
@@ -130,6 +148,7 @@ impl Widget for TokenCachedWidget {
                     priority: 51,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -139,16 +158,16 @@ impl Widget for TokenCachedWidget {
         let text = if config.raw_value {
             format_tokens(val, true)
         } else {
-            format!("Cache: {}", format_tokens(val, false))
+            format!("Cache: {}", format_tokens(val, config.compact))
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 51,
             visible: true,
             color_hint: None,
+            value: Some(val as f64),
         }
     }
 }
@@ -160,7 +179,12 @@ impl Widget for TokenTotalWidget {
         "tokens-total"
     }
 
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
         let usage = match data
             .context_window
             .as_ref()
@@ -174,6 +198,7 @@ impl Widget for TokenTotalWidget {
                     priority: 50,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -185,16 +210,16 @@ impl Widget for TokenTotalWidget {
         let text = if config.raw_value {
             format_tokens(val, true)
         } else {
-            format!("Total: {}", format_tokens(val, false))
+            format!("Total: {}", format_tokens(val, config.compact))
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 50,
             visible: true,
             color_hint: None,
+            value: Some(val as f64),
         }
     }
 }
@@ -1,13 +1,19 @@
 use std::collections::HashMap;
 
 use super::data::SessionData;
+use super::render_context::RenderContext;
 
 pub struct WidgetOutput {
     pub text: String,
+    /// Terminal columns of `text`, measured by the registry after `render`;
+    /// widgets leave it at 0.
     pub display_width: usize,
     pub priority: u8,
     pub visible: bool,
     pub color_hint: Option<String>,
+    /// The number behind the text (cost in USD, percent, count, minutes),
+    /// compared against the widget's `thresholds`.
+    pub value: Option<f64>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -21,9 +27,30 @@ pub struct WidgetConfig {
     pub padding: Option<String>,
     pub merge_next: bool,
     pub metadata: HashMap<String, String>,
+    /// Set by the layout engine when the line is too narrow for the full
+    /// rendering; widgets should drop labels and detail where they can.
+    pub compact: bool,
 }
 
 pub trait Widget: Send + Sync {
     fn name(&self) -> &str;
-    fn render(&self, data: &SessionData, config: &WidgetConfig) -> WidgetOutput;
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput;
+
+    /// Metadata keys this widget reads; anything else in its `metadata`
+    /// table is reported by `validate`.
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether the widget reads `key` from its `metadata` table. Widgets
+    /// whose keys aren't known until runtime override this instead of
+    /// `metadata_keys`.
+    fn accepts_metadata(&self, key: &str) -> bool {
+        self.metadata_keys().contains(&key)
+    }
 }
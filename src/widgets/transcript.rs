@@ -0,0 +1,174 @@
+use super::data::SessionData;
+use super::render_context::RenderContext;
+use super::traits::{Widget, WidgetConfig, WidgetOutput};
+
+pub struct LastToolWidget;
+pub struct ToolCountWidget;
+pub struct LastPromptWidget;
+pub struct IdleTimeWidget;
+
+fn hidden(priority: u8) -> WidgetOutput {
+    WidgetOutput {
+        text: String::new(),
+        display_width: 0,
+        priority,
+        visible: false,
+        color_hint: None,
+        value: None,
+    }
+}
+
+fn shown(text: String, priority: u8) -> WidgetOutput {
+    WidgetOutput {
+        text,
+        display_width: 0,
+        priority,
+        visible: true,
+        color_hint: None,
+        value: None,
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+fn format_idle(secs: i64) -> String {
+    let secs = secs.max(0);
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h{}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+impl Widget for LastToolWidget {
+    fn name(&self) -> &str {
+        "last-tool"
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let tool = match ctx.transcript(data).and_then(|s| s.last_tool.clone()) {
+            Some(t) => t,
+            None => return hidden(35),
+        };
+
+        let text = if config.raw_value || config.compact {
+            tool
+        } else {
+            format!("Tool: {}", tool)
+        };
+        shown(text, 35)
+    }
+}
+
+impl Widget for ToolCountWidget {
+    fn name(&self) -> &str {
+        "tool-count"
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let count = match ctx.transcript(data) {
+            Some(s) => s.tool_count,
+            None => return hidden(30),
+        };
+
+        let text = if config.raw_value || config.compact {
+            count.to_string()
+        } else {
+            format!("Tools: {}", count)
+        };
+        WidgetOutput {
+            value: Some(count as f64),
+            ..shown(text, 30)
+        }
+    }
+}
+
+impl Widget for LastPromptWidget {
+    fn name(&self) -> &str {
+        "last-prompt"
+    }
+
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["max_length"]
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let prompt = match ctx.transcript(data).and_then(|s| s.last_prompt.as_deref()) {
+            Some(p) => p,
+            None => return hidden(20),
+        };
+
+        let max_length: usize = config
+            .metadata
+            .get("max_length")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        let max_length = if config.compact {
+            max_length / 2
+        } else {
+            max_length
+        };
+
+        let prompt = truncate(prompt, max_length.max(1));
+        let text = if config.raw_value || config.compact {
+            prompt
+        } else {
+            format!("\u{201C}{}\u{201D}", prompt)
+        };
+        shown(text, 20)
+    }
+}
+
+impl Widget for IdleTimeWidget {
+    fn name(&self) -> &str {
+        "idle-time"
+    }
+
+    fn render(
+        &self,
+        data: &SessionData,
+        ctx: &RenderContext,
+        config: &WidgetConfig,
+    ) -> WidgetOutput {
+        let last = match ctx.transcript(data).and_then(|s| s.last_assistant_at) {
+            Some(t) => t,
+            None => return hidden(25),
+        };
+
+        let idle_secs = (ctx.now() - last).num_seconds();
+        let idle = format_idle(idle_secs);
+        let text = if config.raw_value || config.compact {
+            idle
+        } else {
+            format!("Idle: {}", idle)
+        };
+        WidgetOutput {
+            value: Some(idle_secs as f64 / 60.0),
+            ..shown(text, 25)
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct VersionWidget;
@@ -8,7 +9,12 @@ impl Widget for VersionWidget {
         "version"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
         let ver = match &data.version {
             Some(v) => v,
             None => {
@@ -18,6 +24,7 @@ impl Widget for VersionWidget {
                     priority: 25,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
@@ -28,13 +35,13 @@ impl Widget for VersionWidget {
             format!("v{}", ver)
         };
 
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 25,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
@@ -1,4 +1,5 @@
 use super::data::SessionData;
+use super::render_context::RenderContext;
 use super::traits::{Widget, WidgetConfig, WidgetOutput};
 
 pub struct VimModeWidget;
@@ -8,7 +9,12 @@ impl Widget for VimModeWidget {
         "vim-mode"
     }
 
-    fn render(&self, data: &SessionData, _config: &WidgetConfig) -> WidgetOutput {
+    fn render(
+        &self,
+        data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
         let vim = match &data.vim {
             Some(v) => v,
             None => {
@@ -18,18 +24,19 @@ impl Widget for VimModeWidget {
                     priority: 95,
                     visible: false,
                     color_hint: None,
+                    value: None,
                 };
             }
         };
 
         let text = vim.mode.clone().unwrap_or_else(|| "NORMAL".to_string());
-        let display_width = text.len();
         WidgetOutput {
             text,
-            display_width,
+            display_width: 0,
             priority: 95,
             visible: true,
             color_hint: None,
+            value: None,
         }
     }
 }
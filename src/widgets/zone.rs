@@ -0,0 +1,33 @@
+use super::data::SessionData;
+use super::render_context::RenderContext;
+use super::traits::{Widget, WidgetConfig, WidgetOutput};
+
+pub struct ZoneWidget;
+
+impl Widget for ZoneWidget {
+    fn name(&self) -> &str {
+        "zone"
+    }
+
+    fn metadata_keys(&self) -> &'static [&'static str] {
+        &["align", "separator", "overflow"]
+    }
+
+    fn render(
+        &self,
+        _data: &SessionData,
+        _ctx: &RenderContext,
+        _config: &WidgetConfig,
+    ) -> WidgetOutput {
+        // A marker with no output of its own; the layout engine splits the
+        // line at each zone and reads its settings from the config.
+        WidgetOutput {
+            text: String::new(),
+            display_width: 0,
+            priority: 100,
+            visible: false,
+            color_hint: None,
+            value: None,
+        }
+    }
+}
@@ -113,3 +113,134 @@ fn config_to_toml_is_valid() {
     assert!(toml_str.contains("default"));
     assert!(toml_str.contains("model"));
 }
+
+#[test]
+fn pricing_overrides_parse_with_derived_cache_rates() {
+    let config: Config = toml::from_str(
+        r#"
+[pricing.opus]
+input = 10.0
+output = 50.0
+
+[pricing.sonnet]
+input = 3.0
+output = 15.0
+cache_write = 4.0
+cache_read = 0.5
+"#,
+    )
+    .unwrap();
+    let opus = config.pricing["opus"];
+    assert_eq!(opus.cache_write, 12.5);
+    assert_eq!(opus.cache_read, 1.0);
+    assert_eq!(config.pricing["sonnet"].cache_write, 4.0);
+
+    let roundtrip: Config = toml::from_str(&config.to_toml()).unwrap();
+    assert_eq!(roundtrip.pricing["sonnet"], config.pricing["sonnet"]);
+}
+
+#[test]
+fn budgets_parse_and_reject_unknown_limits() {
+    let source = r#"
+[budgets]
+"~/work/*" = { daily = 20.0, monthly = 300.0 }
+"~/work/acme" = { weekly = 50.0 }
+"#;
+    let config: Config = toml::from_str(source).unwrap();
+    let work = config.budgets["~/work/*"];
+    assert_eq!(work.daily, Some(20.0));
+    assert_eq!(work.weekly, None);
+    assert_eq!(config.budgets["~/work/acme"].weekly, Some(50.0));
+
+    let roundtrip: Config = toml::from_str(&config.to_toml()).unwrap();
+    assert_eq!(roundtrip.budgets, config.budgets);
+
+    let registry = claude_status::widgets::WidgetRegistry::builtin();
+    let errors = Config::parse_checked(
+        "[budgets]\n\"~/work/*\" = { dayly = 20.0 }\n",
+        &registry,
+        &[],
+    )
+    .unwrap_err();
+    assert!(errors[0].message.contains("dayly"), "{}", errors[0]);
+    assert_eq!(errors[0].line, Some(2));
+}
+
+#[test]
+fn calendar_section_parses_and_reports_bad_values() {
+    use claude_status::calendar::Timezone;
+
+    let config: Config = toml::from_str(
+        r#"
+[calendar]
+timezone = "-08:00"
+week_start = "sunday"
+billing_day = 15
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.calendar.timezone.to_string(), "-08:00");
+    assert_eq!(config.calendar.week_start, chrono::Weekday::Sun);
+    assert_eq!(config.calendar.billing_day, 15);
+    let roundtrip: Config = toml::from_str(&config.to_toml()).unwrap();
+    assert_eq!(roundtrip.calendar, config.calendar);
+
+    // The default calendar is left out of saved configs
+    assert!(!Config::default().to_toml().contains("[calendar]"));
+    assert_eq!(Config::default().calendar.timezone, Timezone::Utc);
+
+    let registry = claude_status::widgets::WidgetRegistry::builtin();
+    for (source, message) in [
+        (
+            "[calendar]\ntimezone = \"Mars/Olympus\"\n",
+            "invalid timezone",
+        ),
+        ("[calendar]\nbilling_day = 32\n", "billing_day"),
+    ] {
+        let errors = Config::parse_checked(source, &registry, &[]).unwrap_err();
+        assert!(errors[0].message.contains(message), "{}", errors[0]);
+        assert_eq!(errors[0].line, Some(2));
+    }
+}
+
+#[test]
+fn default_config_passes_validation() {
+    let registry = claude_status::widgets::WidgetRegistry::builtin();
+    assert!(Config::parse_checked(&Config::default().to_toml(), &registry, &[]).is_ok());
+}
+
+#[test]
+fn load_checked_reports_missing_file() {
+    let registry = claude_status::widgets::WidgetRegistry::builtin();
+    let errors =
+        Config::load_checked(Some("/nonexistent/path/config.toml"), &registry, &[]).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.starts_with("cannot read"));
+    assert_eq!(errors[0].line, None);
+}
+
+#[test]
+fn auto_theme_follows_terminal_background() {
+    use claude_status::render::Background;
+
+    let config: Config = toml::from_str("theme = \"auto\"\nauto_dark = \"nord\"\n").unwrap();
+    assert_eq!(config.theme_name(Some(Background::Light)), "light");
+    assert_eq!(config.theme_name(Some(Background::Dark)), "nord");
+    // Unknown backgrounds are assumed dark
+    assert_eq!(config.theme_name(None), "nord");
+
+    let fixed = Config::default();
+    assert_eq!(fixed.theme_name(Some(Background::Light)), "default");
+}
+
+#[test]
+fn auto_theme_variants_saved_only_when_changed() {
+    let toml_str = Config::default().to_toml();
+    assert!(!toml_str.contains("auto_light"));
+    assert!(!toml_str.contains("auto_dark"));
+
+    let config: Config = toml::from_str("auto_dark = \"nord\"\n").unwrap();
+    let toml_str = config.to_toml();
+    assert!(toml_str.contains("auto_dark = \"nord\""));
+    assert!(!toml_str.contains("auto_light"));
+}
@@ -1,15 +1,15 @@
 use claude_status::config::Config;
 use claude_status::layout::LayoutEngine;
 use claude_status::render::Renderer;
-use claude_status::widgets::{SessionData, WidgetRegistry};
+use claude_status::widgets::{RenderContext, SessionData, WidgetRegistry};
 
 fn render_json(json: &str) -> Vec<String> {
     let data: SessionData = serde_json::from_str(json).expect("Failed to parse JSON");
     let config = Config::default();
     let renderer = Renderer::detect("none");
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let engine = LayoutEngine::new(&config, &renderer);
-    engine.render(&data, &config, &registry)
+    engine.render(&data, &registry)
 }
 
 #[test]
@@ -179,6 +179,8 @@ fn multiline_config_produces_multiple_lines() {
             padding: None,
             merge_next: false,
             metadata: HashMap::new(),
+            thresholds: HashMap::new(),
+            when: None,
         }],
         vec![LineWidgetConfig {
             widget_type: "session-cost".into(),
@@ -190,13 +192,15 @@ fn multiline_config_produces_multiple_lines() {
             padding: None,
             merge_next: false,
             metadata: HashMap::new(),
+            thresholds: HashMap::new(),
+            when: None,
         }],
     ];
 
     let renderer = Renderer::detect("none");
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let engine = LayoutEngine::new(&config, &renderer);
-    let lines = engine.render(&data, &config, &registry);
+    let lines = engine.render(&data, &registry);
     assert_eq!(lines.len(), 2, "Should produce two output lines");
 }
 
@@ -247,7 +251,7 @@ fn renderer_none_produces_no_ansi() {
 
 #[test]
 fn widget_registry_has_all_expected_widgets() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = SessionData::default();
     let config = claude_status::widgets::WidgetConfig {
         widget_type: String::new(),
@@ -259,6 +263,7 @@ fn widget_registry_has_all_expected_widgets() {
         padding: None,
         merge_next: false,
         metadata: std::collections::HashMap::new(),
+        compact: false,
     };
 
     let expected = [
@@ -270,6 +275,8 @@ fn widget_registry_has_all_expected_widgets() {
         "tokens-cached",
         "tokens-total",
         "session-cost",
+        "turn-cost",
+        "cost-compare",
         "session-duration",
         "block-timer",
         "git-branch",
@@ -286,14 +293,22 @@ fn widget_registry_has_all_expected_widgets() {
         "api-duration",
         "custom-command",
         "custom-text",
+        "template",
         "separator",
         "flex-separator",
+        "zone",
         "terminal-width",
+        "last-tool",
+        "tool-count",
+        "last-prompt",
+        "idle-time",
     ];
 
     for name in &expected {
         assert!(
-            registry.render(name, &data, &config).is_some(),
+            registry
+                .render(name, &data, &RenderContext::new(), &config)
+                .is_some(),
             "Widget '{}' should be registered in the registry",
             name
         );
@@ -354,3 +369,584 @@ fn all_themes_have_required_color_roles() {
         }
     }
 }
+
+#[test]
+fn builtin_themes_pair_every_role_with_a_background() {
+    use claude_status::themes::{ROLES, Theme};
+
+    for name in Theme::list() {
+        let theme = Theme::get(name);
+        for role in ROLES {
+            assert!(theme.color(role).is_some(), "{name} has no {role} color");
+            assert!(
+                theme.background(role).is_some(),
+                "{name} has no {role} background"
+            );
+        }
+    }
+}
+
+#[test]
+fn every_rendered_widget_has_a_theme_role() {
+    let registry = WidgetRegistry::builtin();
+    let theme = claude_status::themes::Theme::get("nord");
+    let data = SessionData::default();
+    let config = claude_status::widgets::WidgetConfig::default();
+    for widget_type in registry.widget_types() {
+        if widget_type == "zone" {
+            continue;
+        }
+        assert!(
+            registry
+                .render(widget_type, &data, &RenderContext::new(), &config)
+                .is_some()
+        );
+        assert!(
+            theme.role_for_widget(widget_type).is_some(),
+            "{widget_type} has no theme role"
+        );
+        assert!(theme.background_for_widget(widget_type).is_some());
+    }
+}
+
+#[test]
+fn powerline_segments_use_theme_backgrounds() {
+    let mut config: Config = toml::from_str(
+        r##"
+theme = "nord"
+lines = [[
+  { type = "model" },
+  { type = "version", background_color = "#101010" },
+]]
+"##,
+    )
+    .unwrap();
+    config.flex_mode = "compact".into();
+    config.powerline.enabled = true;
+    let data: SessionData = serde_json::from_value(serde_json::json!({
+        "model": { "display_name": "Opus" },
+        "version": "2.1.31"
+    }))
+    .unwrap();
+    let renderer = Renderer::detect("truecolor");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    let line = engine.render(&data, &registry).remove(0);
+    // nord's model role: #88c0d0 on #434c5e
+    assert!(line.contains("\x1b[48;2;67;76;94m\x1b[38;2;136;192;208m Opus "));
+    // An explicit background still wins
+    assert!(line.contains("\x1b[48;2;16;16;16m"));
+}
+
+// ─── User themes ─────────────────────────────────────────────
+
+#[test]
+fn user_theme_inherits_from_builtin() {
+    use claude_status::themes::{Theme, UserTheme};
+
+    let user = UserTheme::parse("mine", "inherits = \"nord\"\nmodel = \"#ff0000\"\n");
+    assert!(user.errors.is_empty(), "{:?}", user.errors);
+    assert_eq!(user.theme.name, "mine");
+    assert_eq!(user.inherits.as_deref(), Some("nord"));
+    assert_eq!(user.theme.color("model"), Some("#ff0000"));
+    assert_eq!(user.theme.color("cost"), Theme::get("nord").color("cost"));
+}
+
+#[test]
+fn user_theme_reports_missing_and_invalid_roles() {
+    use claude_status::themes::UserTheme;
+
+    let user = UserTheme::parse(
+        "bare",
+        "inherits = \"nord-ish\"\nmodel = \"red\"\ncolour = \"blue\"\ncost = \"reddish\"\n",
+    );
+    assert_eq!(user.errors.len(), 16);
+    assert!(user.errors[0].starts_with("unknown base theme \"nord-ish\""));
+    assert_eq!(user.errors[1], "unknown role \"colour\"");
+    assert_eq!(
+        user.errors[2],
+        "invalid color \"reddish\" for role \"cost\""
+    );
+    assert_eq!(user.errors[3], "missing role \"context_ok\"");
+    assert!(!user.errors.contains(&"missing role \"model\"".to_string()));
+    assert_eq!(user.theme.color("model"), Some("red"));
+}
+
+#[test]
+fn user_theme_roles_take_a_background() {
+    use claude_status::themes::UserTheme;
+
+    let user = UserTheme::parse(
+        "deep",
+        "inherits = \"nord\"\nmodel = { fg = \"#ffffff\", bg = \"#000080\" }\ncost = { fg = \"yellow\" }\ndanger = { fg = \"red\", bg = \"navy-ish\" }\n",
+    );
+    assert_eq!(
+        user.errors,
+        ["invalid background \"navy-ish\" for role \"danger\""]
+    );
+    assert_eq!(user.theme.color("model"), Some("#ffffff"));
+    assert_eq!(user.theme.background("model"), Some("#000080"));
+    // A role without `bg` keeps the base theme's background
+    assert_eq!(user.theme.color("cost"), Some("yellow"));
+    assert_eq!(user.theme.background("cost"), Some("#2e3440"));
+}
+
+#[test]
+fn user_themes_load_from_directory() {
+    use claude_status::themes::UserTheme;
+
+    let dir = std::env::temp_dir().join(format!("claude-status-themes-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("zen.toml"), "inherits = \"light\"\n").unwrap();
+    std::fs::write(dir.join("abyss.toml"), "inherits = \"dracula\"\n").unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+    let names: Vec<String> = UserTheme::load_dir(&dir)
+        .into_iter()
+        .map(|t| t.theme.name)
+        .collect();
+    assert_eq!(names, ["abyss", "zen"]);
+    let zen = UserTheme::find(&dir, "zen").unwrap();
+    assert_eq!(zen.path, dir.join("zen.toml"));
+    assert!(UserTheme::find(&dir, "notes").is_none());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ─── Priority-based widget dropping ──────────────────────────
+
+fn line_widget(widget_type: &str) -> claude_status::config::LineWidgetConfig {
+    claude_status::config::LineWidgetConfig {
+        widget_type: widget_type.into(),
+        id: String::new(),
+        color: None,
+        background_color: None,
+        bold: None,
+        raw_value: false,
+        padding: None,
+        merge_next: false,
+        metadata: std::collections::HashMap::new(),
+        thresholds: std::collections::HashMap::new(),
+        when: None,
+    }
+}
+
+fn custom_text(text: &str) -> claude_status::config::LineWidgetConfig {
+    let mut w = line_widget("custom-text");
+    w.metadata.insert("text".into(), text.into());
+    w
+}
+
+fn render_narrow(
+    lines: Vec<Vec<claude_status::config::LineWidgetConfig>>,
+    powerline: bool,
+) -> Vec<String> {
+    let data: SessionData = serde_json::from_str(
+        r#"{
+            "session_id": "abc12345-def6",
+            "cwd": "/work/a-rather-long-project-directory-name",
+            "model": { "display_name": "Opus" },
+            "version": "2.1.31",
+            "context_window": { "used_percentage": 42.5 }
+        }"#,
+    )
+    .unwrap();
+    let mut config = Config {
+        // "compact" flex mode pins the available width to 60 columns
+        flex_mode: "compact".into(),
+        lines,
+        ..Default::default()
+    };
+    config.powerline.enabled = powerline;
+
+    let renderer = Renderer::detect("none");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    engine.render(&data, &registry)
+}
+
+#[test]
+fn narrow_line_drops_lowest_priority_widgets_first() {
+    let lines = render_narrow(
+        vec![vec![
+            line_widget("model"),
+            custom_text(&"x".repeat(30)),
+            line_widget("session-id"),
+            line_widget("context-percentage"),
+            line_widget("version"),
+        ]],
+        false,
+    );
+    let line = &lines[0];
+    assert!(line.contains("Opus"));
+    assert!(line.contains("42%"));
+    // session-id (20) and version (25) go before custom-text (30)
+    assert!(!line.contains("abc12345"));
+    assert!(!line.contains("v2.1.31"));
+    assert!(line.contains(&"x".repeat(30)));
+    assert!(unicode_width::UnicodeWidthStr::width(line.as_str()) <= 60);
+}
+
+#[test]
+fn wide_enough_line_keeps_all_widgets() {
+    let lines = render_narrow(
+        vec![vec![
+            line_widget("model"),
+            line_widget("session-id"),
+            line_widget("version"),
+        ]],
+        false,
+    );
+    assert_eq!(lines[0], " Opus  |  abc12345  |  v2.1.31 ");
+}
+
+#[test]
+fn merged_widgets_are_dropped_together() {
+    let mut label = custom_text("ver:");
+    label.merge_next = true;
+    let lines = render_narrow(
+        vec![vec![
+            line_widget("model"),
+            label,
+            line_widget("version"),
+            line_widget("cwd"),
+        ]],
+        false,
+    );
+    let line = &lines[0];
+    assert!(!line.contains("ver:"), "merge group should drop as a unit");
+    assert!(!line.contains("v2.1.31"));
+    assert!(line.contains("Opus"));
+    assert!(line.contains("a-rather-long-project-directory-name"));
+}
+
+#[test]
+fn dropping_widget_prunes_dangling_separator() {
+    let mut sep = line_widget("separator");
+    sep.metadata.insert("char".into(), "#".into());
+    let lines = render_narrow(
+        vec![vec![
+            line_widget("model"),
+            custom_text(&"z".repeat(40)),
+            sep,
+            line_widget("session-id"),
+        ]],
+        false,
+    );
+    let line = &lines[0];
+    assert!(!line.contains("abc12345"));
+    assert!(!line.contains('#'), "trailing separator should be removed");
+}
+
+#[test]
+fn powerline_line_drops_to_fit() {
+    let lines = render_narrow(
+        vec![vec![
+            line_widget("model"),
+            custom_text(&"w".repeat(50)),
+            line_widget("session-id"),
+            line_widget("version"),
+        ]],
+        true,
+    );
+    let line = &lines[0];
+    assert!(line.contains("Opus"));
+    assert!(!line.contains("abc12345"));
+    assert!(!line.contains("v2.1.31"));
+}
+
+// ─── Compact rendering ───────────────────────────────────────
+
+fn render_tokens_line(compact_threshold: u8, model: &str, input_tokens: u64) -> String {
+    let data: SessionData = serde_json::from_str(&format!(
+        r#"{{
+            "model": {{ "display_name": "{model}" }},
+            "cost": {{ "total_cost_usd": 0.5, "total_duration_ms": 345000 }},
+            "context_window": {{
+                "current_usage": {{ "input_tokens": {input_tokens}, "output_tokens": 1234567 }}
+            }}
+        }}"#
+    ))
+    .unwrap();
+    let config = Config {
+        flex_mode: "compact".into(),
+        compact_threshold,
+        lines: vec![vec![
+            line_widget("model"),
+            line_widget("tokens-input"),
+            line_widget("tokens-output"),
+            line_widget("session-duration"),
+        ]],
+        ..Default::default()
+    };
+
+    let renderer = Renderer::detect("none");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    engine.render(&data, &registry).remove(0)
+}
+
+#[test]
+fn full_rendering_used_when_line_fits() {
+    let line = render_tokens_line(0, "Opus", 8500);
+    assert!(line.contains("In: 8,500"), "{line}");
+    assert!(line.contains("5m 45s"), "{line}");
+}
+
+#[test]
+fn overflowing_line_falls_back_to_compact_before_dropping() {
+    // Full: 69 columns, compact: 58 columns
+    let line = render_tokens_line(0, "Claude Opus 4.6 1M", 98_765_432);
+    assert!(line.contains("In: 98.8M"), "{line}");
+    assert!(line.contains("Out: 1.2M"), "{line}");
+    assert!(line.contains("5m45s"), "{line}");
+    assert!(line.contains("Claude Opus 4.6 1M"), "{line}");
+}
+
+#[test]
+fn width_below_compact_threshold_renders_compact() {
+    let line = render_tokens_line(80, "Opus", 8500);
+    assert!(line.contains("In: 8K"), "{line}");
+    assert!(line.contains("5m45s"), "{line}");
+}
+
+// ─── Pricing ─────────────────────────────────────────────────
+
+#[test]
+fn pricing_overrides_reach_cost_widgets() {
+    let mut config: Config = toml::from_str(
+        r#"
+[pricing.opus]
+input = 1.0
+output = 1.0
+cache_write = 1.0
+cache_read = 1.0
+"#,
+    )
+    .unwrap();
+    config.lines = vec![vec![line_widget("turn-cost")]];
+    let data: SessionData = serde_json::from_str(
+        r#"{
+            "model": { "id": "claude-opus-4-6", "display_name": "Opus" },
+            "context_window": {
+                "current_usage": {
+                    "input_tokens": 250000,
+                    "output_tokens": 250000,
+                    "cache_creation_input_tokens": 250000,
+                    "cache_read_input_tokens": 250000
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let renderer = Renderer::detect("none");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    let lines = engine.render(&data, &registry);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].trim(), "$1.000");
+}
+
+// ─── Visibility rules ────────────────────────────────────────
+
+#[test]
+fn when_rules_hide_widgets_until_they_hold() {
+    let mut warning = custom_text("CTX HIGH");
+    warning.when = Some("context_window.used_percentage > 40".into());
+    let mut sonnet_only = custom_text("SONNET");
+    sonnet_only.when = Some("model.display_name contains 'Sonnet'".into());
+    let mut broken = custom_text("BROKEN");
+    broken.when = Some("model.id ==".into());
+
+    let lines = render_narrow(
+        vec![vec![line_widget("model"), warning, sonnet_only, broken]],
+        false,
+    );
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("CTX HIGH"));
+    assert!(!lines[0].contains("SONNET"));
+    assert!(!lines[0].contains("BROKEN"));
+}
+
+#[test]
+fn widgets_and_rules_share_the_render_context() {
+    use claude_status::git::GitSnapshot;
+
+    let mut dirty_note = custom_text("DIRTY");
+    dirty_note.when = Some("git.dirty".into());
+    let config = Config {
+        flex_mode: "full".into(),
+        lines: vec![vec![
+            line_widget("git-branch"),
+            dirty_note,
+            line_widget("terminal-width"),
+        ]],
+        ..Default::default()
+    };
+    let snapshot = GitSnapshot {
+        branch: Some("feature/ctx".into()),
+        modified: 2,
+        ..Default::default()
+    };
+    let ctx = RenderContext::new()
+        .with_git(Some(snapshot))
+        .with_terminal_width(100);
+
+    let renderer = Renderer::detect("none");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    let lines = engine.render_with_context(&SessionData::default(), &ctx, &registry);
+    assert_eq!(lines, [" feature/ctx  |  DIRTY  |  100 cols "]);
+}
+
+// ─── Threshold colors ────────────────────────────────────────
+
+fn render_cost_with_thresholds(cost: f64) -> String {
+    let mut config: Config = toml::from_str(
+        r#"
+lines = [[
+  { type = "session-cost", color = "green", thresholds = { yellow = 1, red = 5.0 } },
+]]
+"#,
+    )
+    .unwrap();
+    config.flex_mode = "compact".into();
+    let data: SessionData =
+        serde_json::from_value(serde_json::json!({ "cost": { "total_cost_usd": cost } })).unwrap();
+    let renderer = Renderer::detect("16");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    engine.render(&data, &registry).remove(0)
+}
+
+#[test]
+fn thresholds_override_color_once_crossed() {
+    assert!(render_cost_with_thresholds(0.50).contains("\x1b[32m"));
+    assert!(render_cost_with_thresholds(1.00).contains("\x1b[33m"));
+    assert!(render_cost_with_thresholds(7.25).contains("\x1b[31m"));
+}
+
+// ─── Zones ───────────────────────────────────────────────────
+
+fn render_zones(source: &str, powerline: bool) -> String {
+    let mut config: Config = toml::from_str(source).unwrap();
+    // "compact" flex mode pins the available width to 60 columns
+    config.flex_mode = "compact".into();
+    config.powerline.enabled = powerline;
+    let data: SessionData = serde_json::from_value(serde_json::json!({
+        "session_id": "abc12345-def6",
+        "model": { "display_name": "Opus" },
+        "version": "2.1.31"
+    }))
+    .unwrap();
+    let renderer = Renderer::detect("none");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    engine.render(&data, &registry).remove(0)
+}
+
+#[test]
+fn zones_align_left_center_and_right() {
+    let line = render_zones(
+        r#"
+lines = [[
+  { type = "model" },
+  { type = "zone", metadata = { align = "center" } },
+  { type = "custom-text", metadata = { text = "mid" } },
+  { type = "zone", metadata = { align = "right", separator = " · " } },
+  { type = "session-id" },
+  { type = "version" },
+]]
+"#,
+        false,
+    );
+    assert!(line.starts_with(" Opus "));
+    assert!(line.ends_with(" abc12345  ·  v2.1.31 "));
+    assert_eq!(line.find("mid"), Some(28));
+    assert_eq!(unicode_width::UnicodeWidthStr::width(line.as_str()), 60);
+}
+
+#[test]
+fn zones_shrink_sides_by_priority_before_the_center() {
+    let long = "x".repeat(45);
+    let line = render_zones(
+        &format!(
+            r#"
+lines = [[
+  {{ type = "model" }},
+  {{ type = "session-id" }},
+  {{ type = "zone", metadata = {{ align = "center" }} }},
+  {{ type = "custom-text", metadata = {{ text = "{long}" }} }},
+  {{ type = "zone", metadata = {{ align = "right" }} }},
+  {{ type = "version" }},
+]]
+"#
+        ),
+        false,
+    );
+    // session-id (20) and version (25) are dropped; the center (30) stays
+    assert!(line.contains("Opus"));
+    assert!(line.contains(&long));
+    assert!(!line.contains("abc12345"));
+    assert!(!line.contains("v2.1.31"));
+}
+
+#[test]
+fn zone_overflow_policies() {
+    let long = "y".repeat(70);
+    let truncated = render_zones(
+        &format!(
+            r#"
+lines = [[
+  {{ type = "model" }},
+  {{ type = "zone", metadata = {{ overflow = "truncate" }} }},
+  {{ type = "custom-text", metadata = {{ text = "{long}" }} }},
+]]
+"#
+        ),
+        false,
+    );
+    assert!(truncated.starts_with(" Opus "));
+    assert!(truncated.ends_with("y… "));
+    assert_eq!(
+        unicode_width::UnicodeWidthStr::width(truncated.as_str()),
+        60
+    );
+
+    let hidden = render_zones(
+        &format!(
+            r#"
+lines = [[
+  {{ type = "model" }},
+  {{ type = "zone", metadata = {{ overflow = "hide" }} }},
+  {{ type = "version" }},
+  {{ type = "custom-text", metadata = {{ text = "{long}" }} }},
+]]
+"#
+        ),
+        false,
+    );
+    // The whole zone goes, though the version alone would have fit
+    assert!(hidden.contains("Opus"));
+    assert!(!hidden.contains("v2.1.31"));
+}
+
+#[test]
+fn powerline_zones_fill_the_line() {
+    let line = render_zones(
+        r#"
+lines = [[
+  { type = "model" },
+  { type = "zone", metadata = { align = "center" } },
+  { type = "version" },
+  { type = "zone", metadata = { align = "right" } },
+  { type = "session-id" },
+]]
+"#,
+        true,
+    );
+    assert!(line.contains("Opus"));
+    assert!(line.contains("v2.1.31"));
+    assert!(line.contains("abc12345"));
+    assert_eq!(unicode_width::UnicodeWidthStr::width(line.as_str()), 60);
+}
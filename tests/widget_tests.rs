@@ -1,5 +1,7 @@
 use claude_status::widgets::data::*;
-use claude_status::widgets::{SessionData, WidgetConfig, WidgetRegistry};
+use claude_status::widgets::{
+    RenderContext, SessionData, Widget, WidgetConfig, WidgetOutput, WidgetRegistry,
+};
 use std::collections::HashMap;
 
 fn mock_session() -> SessionData {
@@ -42,6 +44,7 @@ fn mock_session() -> SessionData {
         exceeds_200k_tokens: Some(false),
         vim: None,
         agent: None,
+        raw: None,
     }
 }
 
@@ -56,6 +59,7 @@ fn default_config() -> WidgetConfig {
         padding: None,
         merge_next: false,
         metadata: HashMap::new(),
+        compact: false,
     }
 }
 
@@ -67,31 +71,37 @@ fn empty_session() -> SessionData {
 
 #[test]
 fn model_widget_renders_display_name() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("model", &data, &config).unwrap();
+    let output = registry
+        .render("model", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "Opus");
 }
 
 #[test]
 fn model_widget_raw_value_renders_model_id() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("model", &data, &config).unwrap();
+    let output = registry
+        .render("model", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "claude-opus-4-6");
 }
 
 #[test]
 fn model_widget_invisible_when_model_is_none() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("model", &data, &config).unwrap();
+    let output = registry
+        .render("model", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -99,11 +109,11 @@ fn model_widget_invisible_when_model_is_none() {
 
 #[test]
 fn context_percentage_renders_percentage() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "42%");
@@ -111,12 +121,12 @@ fn context_percentage_renders_percentage() {
 
 #[test]
 fn context_percentage_bar_mode() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("bar".into(), "true".into());
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert!(output.visible);
     // 42.5% -> round(4.25) = 4 filled, 6 empty
@@ -127,25 +137,27 @@ fn context_percentage_bar_mode() {
 
 #[test]
 fn context_percentage_inverse_mode() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("inverse".into(), "true".into());
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert!(output.visible);
     // 100 - 42.5 = 57.5, truncated to 57
     assert_eq!(output.text, "57%");
+    // The untruncated figure is exposed for thresholds
+    assert_eq!(output.value, Some(57.5));
 }
 
 #[test]
 fn context_percentage_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert!(!output.visible);
 }
@@ -154,10 +166,12 @@ fn context_percentage_invisible_without_data() {
 
 #[test]
 fn context_length_renders_compact() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("context-length", &data, &config).unwrap();
+    let output = registry
+        .render("context-length", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // input=8500 + cache_creation=5000 + cache_read=2000 = 15500 -> "15K"
     assert_eq!(output.text, "15K");
@@ -165,21 +179,25 @@ fn context_length_renders_compact() {
 
 #[test]
 fn context_length_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("context-length", &data, &config).unwrap();
+    let output = registry
+        .render("context-length", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "15500");
 }
 
 #[test]
 fn context_length_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("context-length", &data, &config).unwrap();
+    let output = registry
+        .render("context-length", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -187,31 +205,37 @@ fn context_length_invisible_without_data() {
 
 #[test]
 fn token_input_renders_formatted() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("tokens-input", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-input", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "In: 8,500");
 }
 
 #[test]
 fn token_input_raw_value_renders_compact() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("tokens-input", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-input", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "8K");
 }
 
 #[test]
 fn token_input_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("tokens-input", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-input", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -219,21 +243,25 @@ fn token_input_invisible_without_data() {
 
 #[test]
 fn token_output_renders_formatted() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("tokens-output", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-output", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "Out: 1,200");
 }
 
 #[test]
 fn token_output_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("tokens-output", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-output", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "1K");
 }
@@ -242,10 +270,12 @@ fn token_output_raw_value() {
 
 #[test]
 fn token_cached_renders_sum() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("tokens-cached", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-cached", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // 5000 + 2000 = 7000
     assert_eq!(output.text, "Cache: 7,000");
@@ -253,11 +283,13 @@ fn token_cached_renders_sum() {
 
 #[test]
 fn token_cached_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("tokens-cached", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-cached", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "7K");
 }
@@ -266,10 +298,12 @@ fn token_cached_raw_value() {
 
 #[test]
 fn token_total_renders_all_tokens() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("tokens-total", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-total", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // 8500 + 1200 + 5000 + 2000 = 16700
     assert_eq!(output.text, "Total: 16,700");
@@ -277,57 +311,126 @@ fn token_total_renders_all_tokens() {
 
 #[test]
 fn token_total_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("tokens-total", &data, &config).unwrap();
+    let output = registry
+        .render("tokens-total", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "16K");
 }
 
+#[test]
+fn token_widgets_compact_keep_label_with_short_numbers() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let mut config = default_config();
+    config.compact = true;
+    let output = registry
+        .render("tokens-input", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "In: 8K");
+    let output = registry
+        .render("tokens-total", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "Total: 16K");
+}
+
 // ─── SessionCostWidget ────────────────────────────────────────
 
 #[test]
 fn session_cost_renders_formatted() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("session-cost", &data, &config).unwrap();
+    let output = registry
+        .render("session-cost", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "$0.08");
 }
 
 #[test]
 fn session_cost_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("session-cost", &data, &config).unwrap();
+    let output = registry
+        .render("session-cost", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
 #[test]
 fn session_cost_with_burn_rate() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("burn_rate".into(), "true".into());
-    let output = registry.render("session-cost", &data, &config).unwrap();
+    let output = registry
+        .render("session-cost", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // $0.08 with burn rate: 0.0842 / (345000/3600000) = 0.0842/0.09583... = ~$0.88/hr
     assert!(output.text.contains("$0.08"));
     assert!(output.text.contains("/hr"));
 }
 
+// ─── TurnCostWidget / CostCompareWidget ───────────────────────
+
+#[test]
+fn turn_cost_prices_current_usage_at_model_rates() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let config = default_config();
+    let output = registry
+        .render("turn-cost", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(output.visible);
+    // Opus 4.6: 8.5K in @5 + 1.2K out @25 + 5K write @6.25 + 2K read @0.5 = $0.105
+    assert!(
+        output.text.starts_with("Turn: $0.10"),
+        "got: {}",
+        output.text
+    );
+}
+
+#[test]
+fn cost_compare_scales_session_cost_to_target_model() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let config = default_config();
+    let output = registry
+        .render("cost-compare", &data, &RenderContext::new(), &config)
+        .unwrap();
+    // Sonnet costs 60% of Opus 4.6 for every token kind
+    assert_eq!(output.text, "Sonnet: $0.05");
+}
+
+#[test]
+fn cost_compare_hidden_when_already_on_target() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let mut config = default_config();
+    config.metadata.insert("model".into(), "opus".into());
+    let output = registry
+        .render("cost-compare", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(!output.visible);
+}
+
 // ─── SessionDurationWidget ────────────────────────────────────
 
 #[test]
 fn session_duration_renders_formatted() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("session-duration", &data, &config).unwrap();
+    let output = registry
+        .render("session-duration", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // 345000ms = 345s = 5m 45s
     assert_eq!(output.text, "5m 45s");
@@ -335,56 +438,144 @@ fn session_duration_renders_formatted() {
 
 #[test]
 fn session_duration_raw_value_compact() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("session-duration", &data, &config).unwrap();
+    let output = registry
+        .render("session-duration", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "5m45s");
 }
 
 #[test]
 fn session_duration_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("session-duration", &data, &config).unwrap();
+    let output = registry
+        .render("session-duration", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
+#[test]
+fn session_duration_compact() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let mut config = default_config();
+    config.compact = true;
+    config.metadata.insert("api_ratio".into(), "true".into());
+    let output = registry
+        .render("session-duration", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "5m45s");
+}
+
 // ─── BlockTimerWidget ─────────────────────────────────────────
 
+/// 11:47, with history since 09:20 putting the block at 09:00–14:00.
+fn block_context() -> RenderContext {
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-11T11:47:00Z")
+        .unwrap()
+        .to_utc();
+    let ts = now.timestamp();
+    let tracker = tracker_with_costs(ts, &[(2 * 3600 + 27 * 60, 1.0), (3600, 2.0)]);
+    RenderContext::new().with_now(now).with_tracker(tracker)
+}
+
 #[test]
 fn block_timer_renders_remaining() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("block-timer", &data, &config).unwrap();
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
     assert!(output.visible);
-    // 345000ms elapsed in block. 18_000_000 - 345000 = 17_655_000ms remaining
-    // 17_655_000 / 60_000 = 294.25 mins -> 4h54m
-    assert!(output.text.contains("Block:"));
-    assert!(output.text.contains("left"));
+    assert_eq!(output.text, "Block: 2h13m left");
+    assert_eq!(output.color_hint, None);
+    // 2h47m of 5h
+    let value = output.value.unwrap();
+    assert!((value - 55.67).abs() < 0.01, "{value}");
 }
 
 #[test]
 fn block_timer_bar_mode() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("bar".into(), "true".into());
-    let output = registry.render("block-timer", &data, &config).unwrap();
+    config.metadata.insert("bar_width".into(), "10".into());
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
     assert!(output.visible);
-    assert!(output.text.contains('▓') || output.text.contains('░'));
+    assert_eq!(output.text, "▓▓▓▓▓▓░░░░ 2h13m");
+}
+
+#[test]
+fn block_timer_details_include_unrecorded_usage() {
+    let registry = WidgetRegistry::builtin();
+    // This session has spent $1 and 10K tokens since it was last recorded
+    let mut data = mock_session();
+    data.cost.as_mut().unwrap().total_cost_usd = Some(1.0842);
+    data.context_window.as_mut().unwrap().total_input_tokens = Some(25234);
+    let mut config = default_config();
+    config.metadata.insert("details".into(), "true".into());
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
+    assert_eq!(output.text, "Block 09:00: 2h13m left, $4.00, 10K tok");
+
+    // The start time is shown in the calendar's time zone
+    let calendar = claude_status::calendar::Calendar {
+        timezone: "-08:00".to_string().try_into().unwrap(),
+        ..Default::default()
+    };
+    let ctx = block_context().with_calendar(calendar);
+    let output = registry
+        .render("block-timer", &data, &ctx, &config)
+        .unwrap();
+    assert_eq!(output.text, "Block 01:00: 2h13m left, $4.00, 10K tok");
+}
+
+#[test]
+fn block_timer_warns_when_projection_passes_cap() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let mut config = default_config();
+    // $3 in 2h47m is on course for $5.39 by 14:00
+    config.metadata.insert("cost_cap".into(), "6".into());
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
+    assert_eq!(output.text, "Block: 2h13m left");
+    assert_eq!(output.color_hint, None);
+
+    config.metadata.insert("cost_cap".into(), "5".into());
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
+    assert_eq!(output.text, "Block: 2h13m left \u{26A0} ~$5.39 > $5.00");
+    assert_eq!(output.color_hint.as_deref(), Some("red"));
+
+    config.compact = true;
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
+    assert_eq!(output.text, "2h13m \u{26A0}");
 }
 
 #[test]
 fn block_timer_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("block-timer", &data, &config).unwrap();
+    let output = registry
+        .render("block-timer", &data, &block_context(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -392,17 +583,19 @@ fn block_timer_invisible_without_data() {
 
 #[test]
 fn cwd_renders_basename() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("cwd", &data, &config).unwrap();
+    let output = registry
+        .render("cwd", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "project");
 }
 
 #[test]
 fn cwd_fish_style() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     // Use a path that won't be abbreviated by home dir
     data.workspace = Some(Workspace {
@@ -412,7 +605,9 @@ fn cwd_fish_style() {
     data.cwd = Some("/var/log/myapp".into());
     let mut config = default_config();
     config.metadata.insert("fish_style".into(), "true".into());
-    let output = registry.render("cwd", &data, &config).unwrap();
+    let output = registry
+        .render("cwd", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // /var/log/myapp -> /v/l/myapp
     assert_eq!(output.text, "/v/l/myapp");
@@ -420,7 +615,7 @@ fn cwd_fish_style() {
 
 #[test]
 fn cwd_full_mode() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.workspace = Some(Workspace {
         current_dir: Some("/var/log/myapp".into()),
@@ -428,48 +623,78 @@ fn cwd_full_mode() {
     });
     let mut config = default_config();
     config.metadata.insert("full".into(), "true".into());
-    let output = registry.render("cwd", &data, &config).unwrap();
+    let output = registry
+        .render("cwd", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "/var/log/myapp");
 }
 
 #[test]
 fn cwd_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = empty_session();
     data.workspace = None;
     data.cwd = None;
     let config = default_config();
-    let output = registry.render("cwd", &data, &config).unwrap();
+    let output = registry
+        .render("cwd", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
+#[test]
+fn cwd_compact_abbreviates_multi_segment_paths() {
+    let registry = WidgetRegistry::builtin();
+    let mut data = mock_session();
+    data.workspace = None;
+    data.cwd = Some("/opt/work/project".into());
+    let mut config = default_config();
+    config.compact = true;
+    config.metadata.insert("full".into(), "true".into());
+    let output = registry
+        .render("cwd", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "/o/w/project");
+
+    // Basename mode is already as short as it gets
+    config.metadata.clear();
+    let output = registry
+        .render("cwd", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "project");
+}
+
 // ─── LinesChangedWidget ──────────────────────────────────────
 
 #[test]
 fn lines_changed_renders_diff() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("lines-changed", &data, &config).unwrap();
+    let output = registry
+        .render("lines-changed", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "+156 -23");
 }
 
 #[test]
 fn lines_changed_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("lines-changed", &data, &config).unwrap();
+    let output = registry
+        .render("lines-changed", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "+156-23");
 }
 
 #[test]
 fn lines_changed_invisible_when_zero() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.cost = Some(Cost {
         total_cost_usd: Some(0.0),
@@ -479,7 +704,9 @@ fn lines_changed_invisible_when_zero() {
         total_lines_removed: Some(0),
     });
     let config = default_config();
-    let output = registry.render("lines-changed", &data, &config).unwrap();
+    let output = registry
+        .render("lines-changed", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -487,31 +714,37 @@ fn lines_changed_invisible_when_zero() {
 
 #[test]
 fn version_renders_with_prefix() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("version", &data, &config).unwrap();
+    let output = registry
+        .render("version", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "v2.1.31");
 }
 
 #[test]
 fn version_already_has_v_prefix() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.version = Some("v3.0.0".into());
     let config = default_config();
-    let output = registry.render("version", &data, &config).unwrap();
+    let output = registry
+        .render("version", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "v3.0.0");
 }
 
 #[test]
 fn version_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("version", &data, &config).unwrap();
+    let output = registry
+        .render("version", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -519,20 +752,24 @@ fn version_invisible_without_data() {
 
 #[test]
 fn session_id_renders_short() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("session-id", &data, &config).unwrap();
+    let output = registry
+        .render("session-id", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "abc12345");
 }
 
 #[test]
 fn session_id_invisible_without_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
-    let output = registry.render("session-id", &data, &config).unwrap();
+    let output = registry
+        .render("session-id", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -540,33 +777,39 @@ fn session_id_invisible_without_data() {
 
 #[test]
 fn vim_mode_invisible_without_vim_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session(); // vim: None
     let config = default_config();
-    let output = registry.render("vim-mode", &data, &config).unwrap();
+    let output = registry
+        .render("vim-mode", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
 #[test]
 fn vim_mode_visible_with_vim_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.vim = Some(Vim {
         mode: Some("INSERT".into()),
     });
     let config = default_config();
-    let output = registry.render("vim-mode", &data, &config).unwrap();
+    let output = registry
+        .render("vim-mode", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "INSERT");
 }
 
 #[test]
 fn vim_mode_defaults_to_normal() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.vim = Some(Vim { mode: None });
     let config = default_config();
-    let output = registry.render("vim-mode", &data, &config).unwrap();
+    let output = registry
+        .render("vim-mode", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "NORMAL");
 }
@@ -575,35 +818,41 @@ fn vim_mode_defaults_to_normal() {
 
 #[test]
 fn agent_name_invisible_by_default() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session(); // agent: None
     let config = default_config();
-    let output = registry.render("agent-name", &data, &config).unwrap();
+    let output = registry
+        .render("agent-name", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
 #[test]
 fn agent_name_visible_with_agent_data() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.agent = Some(Agent {
         name: Some("researcher".into()),
     });
     let config = default_config();
-    let output = registry.render("agent-name", &data, &config).unwrap();
+    let output = registry
+        .render("agent-name", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "researcher");
 }
 
 #[test]
 fn agent_name_invisible_with_empty_name() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.agent = Some(Agent {
         name: Some("".into()),
     });
     let config = default_config();
-    let output = registry.render("agent-name", &data, &config).unwrap();
+    let output = registry
+        .render("agent-name", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -611,31 +860,37 @@ fn agent_name_invisible_with_empty_name() {
 
 #[test]
 fn exceeds_tokens_invisible_when_false() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session(); // exceeds_200k_tokens: Some(false)
     let config = default_config();
-    let output = registry.render("exceeds-tokens", &data, &config).unwrap();
+    let output = registry
+        .render("exceeds-tokens", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
 #[test]
 fn exceeds_tokens_visible_when_true() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.exceeds_200k_tokens = Some(true);
     let config = default_config();
-    let output = registry.render("exceeds-tokens", &data, &config).unwrap();
+    let output = registry
+        .render("exceeds-tokens", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "!200K");
 }
 
 #[test]
 fn exceeds_tokens_invisible_when_none() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.exceeds_200k_tokens = None;
     let config = default_config();
-    let output = registry.render("exceeds-tokens", &data, &config).unwrap();
+    let output = registry
+        .render("exceeds-tokens", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
@@ -643,53 +898,346 @@ fn exceeds_tokens_invisible_when_none() {
 
 #[test]
 fn custom_text_renders_metadata_text() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("text".into(), "Hello World".into());
-    let output = registry.render("custom-text", &data, &config).unwrap();
+    let output = registry
+        .render("custom-text", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "Hello World");
 }
 
 #[test]
 fn custom_text_invisible_without_text() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("custom-text", &data, &config).unwrap();
+    let output = registry
+        .render("custom-text", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
 #[test]
 fn custom_text_invisible_with_empty_text() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("text".into(), "".into());
-    let output = registry.render("custom-text", &data, &config).unwrap();
+    let output = registry
+        .render("custom-text", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(!output.visible);
+}
+
+// ─── CustomCommandWidget ──────────────────────────────────────
+
+fn command_config(command: &str) -> WidgetConfig {
+    let mut config = default_config();
+    config.metadata.insert("command".into(), command.into());
+    config
+}
+
+fn unique_session(tag: &str) -> SessionData {
+    let mut data = mock_session();
+    data.session_id = Some(format!(
+        "{tag}-{}-{:?}",
+        std::process::id(),
+        std::time::Instant::now()
+    ));
+    data
+}
+
+#[test]
+fn custom_command_receives_session_json_and_env() {
+    let registry = WidgetRegistry::builtin();
+    let mut data = unique_session("stdin");
+    data.raw = Some(r#"{"model":{"id":"from-stdin"}}"#.into());
+    let config = command_config(
+        r#"printf '%s %s' "$(sed 's/.*"id":"\([^"]*\)".*/\1/')" "$CLAUDE_STATUS_MODEL_NAME""#,
+    );
+    let output = registry
+        .render("custom-command", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(output.visible);
+    assert_eq!(output.text, "from-stdin Opus");
+}
+
+#[test]
+fn custom_command_strips_ansi_unless_asked() {
+    let registry = WidgetRegistry::builtin();
+    let data = unique_session("ansi");
+    let mut config = command_config(r"printf '\033[31mred\033[0m'");
+    let output = registry
+        .render("custom-command", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "red");
+
+    config.metadata.insert("ansi".into(), "true".into());
+    config.metadata.insert("cache_ttl".into(), "0".into());
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let output = registry
+        .render("custom-command", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "\x1b[31mred\x1b[0m");
+    assert_eq!(output.display_width, 3);
+}
+
+#[test]
+fn custom_command_timeout_falls_back_to_last_output() {
+    let registry = WidgetRegistry::builtin();
+    let data = unique_session("timeout");
+    let marker = std::env::temp_dir().join(format!("claude-status-hang-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    // Answers once, then hangs on every later run
+    let mut config = command_config(&format!(
+        "if [ -f {m} ]; then sleep 5; else touch {m}; echo first; fi",
+        m = marker.display()
+    ));
+    config.metadata.insert("timeout_ms".into(), "200".into());
+    config.metadata.insert("cache_ttl".into(), "0".into());
+
+    let output = registry
+        .render("custom-command", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "first");
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let started = std::time::Instant::now();
+    let output = registry
+        .render("custom-command", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert!(output.visible);
+    assert_eq!(output.text, "first");
+
+    let _ = std::fs::remove_file(&marker);
+}
+
+#[test]
+fn custom_command_background_child_does_not_hold_render() {
+    let registry = WidgetRegistry::builtin();
+    let data = unique_session("background");
+    // The backgrounded sleep inherits stdout and keeps it open
+    let mut config = command_config("sleep 5 & echo started");
+    config.metadata.insert("timeout_ms".into(), "300".into());
+
+    let started = std::time::Instant::now();
+    let output = registry
+        .render("custom-command", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert!(output.visible);
+    assert_eq!(output.text, "started");
+}
+
+// ─── PluginWidget ─────────────────────────────────────────────
+
+/// A plugins directory with one executable per `(file name, script)`.
+fn plugin_dir(tag: &str, plugins: &[(&str, &str)]) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!(
+        "claude-status-plugins-{tag}-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (name, script) in plugins {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    dir
+}
+
+const TICKET_PLUGIN: &str = r#"if [ "$1" = "--describe" ]; then
+  echo '{"name": "ticket", "metadata": ["project"]}'
+  exit
+fi
+case "$(cat)" in
+  *'"project":"OPS"'*'"display_name":"Opus"'*) echo '{"text": "OPS-42", "priority": 70, "color_hint": "red"}' ;;
+  *) echo '{"text": ""}' ;;
+esac"#;
+
+#[test]
+fn plugin_renders_reply_to_session_and_metadata() {
+    let dir = plugin_dir("render", &[("ticket.sh", TICKET_PLUGIN)]);
+    let mut registry = WidgetRegistry::builtin();
+    registry.register_plugins(&dir);
+
+    let mut data = unique_session("plugin");
+    data.raw = Some(serde_json::to_string(&data).unwrap());
+    let mut config = default_config();
+    config.metadata.insert("project".into(), "OPS".into());
+    let output = registry
+        .render("ticket", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(output.visible);
+    assert_eq!(output.text, "OPS-42");
+    assert_eq!(output.display_width, 6);
+    assert_eq!(output.priority, 70);
+    assert_eq!(output.color_hint.as_deref(), Some("red"));
+
+    config.metadata.insert("project".into(), "WEB".into());
+    let output = registry
+        .render("ticket", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn plugins_declare_name_and_metadata_keys() {
+    let dir = plugin_dir(
+        "describe",
+        &[
+            ("ticket", TICKET_PLUGIN),
+            // Built-in widgets can't be replaced
+            ("model", r#"echo '{"name": "model"}'"#),
+            ("broken", "echo not json"),
+        ],
+    );
+    std::fs::write(dir.join("notes.txt"), "not executable").unwrap();
+    let mut registry = WidgetRegistry::builtin();
+    registry.register_plugins(&dir);
+
+    assert_eq!(registry.accepts_metadata("ticket", "project"), Some(true));
+    assert_eq!(
+        registry.accepts_metadata("ticket", "timeout_ms"),
+        Some(true)
+    );
+    assert_eq!(registry.accepts_metadata("ticket", "projcet"), Some(false));
+    assert!(!registry.contains("broken"));
+    let output = registry
+        .render(
+            "model",
+            &mock_session(),
+            &RenderContext::new(),
+            &default_config(),
+        )
+        .unwrap();
+    assert_eq!(output.text, "Opus");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ─── TemplateWidget ───────────────────────────────────────────
+
+fn render_template(template: &str, data: &SessionData) -> String {
+    let registry = WidgetRegistry::builtin();
+    let mut config = default_config();
+    config.metadata.insert("template".into(), template.into());
+    let output = registry
+        .render("template", data, &RenderContext::new(), &config)
+        .unwrap();
+    if output.visible {
+        output.text
+    } else {
+        String::new()
+    }
+}
+
+#[test]
+fn template_interpolates_fields_with_format_specs() {
+    let data = mock_session();
+    assert_eq!(
+        render_template("{model.display_name} @ ${cost.total_cost_usd:.3}", &data),
+        "Opus @ $0.084"
+    );
+    assert_eq!(
+        render_template("[{version:>8}] {output_style.name:.3}", &data),
+        "  2.1.31 def"
+    );
+}
+
+#[test]
+fn template_defaults_and_conditional_sections() {
+    let data = mock_session();
+    assert_eq!(render_template("{vim.mode|INSERT}", &data), "INSERT");
+    assert_eq!(
+        render_template("{model.display_name}[ ({agent.name})]", &data),
+        "Opus"
+    );
+    assert_eq!(render_template("{{literal}} [[x]]", &data), "{literal} [x]");
+}
+
+#[test]
+fn template_includes_other_widgets() {
+    let data = mock_session();
+    assert_eq!(
+        render_template("{widget:session-cost} / {widget:lines-changed}", &data),
+        "$0.08 / +156 -23"
+    );
+    // Hidden widgets count as missing
+    assert_eq!(render_template("[{widget:vim-mode}]", &data), "");
+}
+
+#[test]
+fn template_includes_widgets_from_the_rendering_registry() {
+    struct Greeting;
+    impl Widget for Greeting {
+        fn name(&self) -> &str {
+            "greeting"
+        }
+        fn render(&self, _: &SessionData, _: &RenderContext, _: &WidgetConfig) -> WidgetOutput {
+            WidgetOutput {
+                text: "hello".into(),
+                display_width: 0,
+                priority: 50,
+                visible: true,
+                color_hint: None,
+                value: None,
+            }
+        }
+    }
+
+    let mut registry = WidgetRegistry::builtin();
+    registry.register(Box::new(Greeting));
+    let mut config = default_config();
+    config
+        .metadata
+        .insert("template".into(), "{widget:greeting}!".into());
+    let output = registry
+        .render("template", &mock_session(), &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "hello!");
+}
+
+#[test]
+fn template_invisible_when_everything_is_missing() {
+    assert_eq!(
+        render_template("[{model.display_name}]", &empty_session()),
+        ""
+    );
 }
 
 // ─── SeparatorWidget ──────────────────────────────────────────
 
 #[test]
 fn separator_renders_default_pipe() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("separator", &data, &config).unwrap();
+    let output = registry
+        .render("separator", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "|");
 }
 
 #[test]
 fn separator_renders_custom_char() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("char".into(), "::".into());
-    let output = registry.render("separator", &data, &config).unwrap();
+    let output = registry
+        .render("separator", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "::");
 }
@@ -698,10 +1246,12 @@ fn separator_renders_custom_char() {
 
 #[test]
 fn terminal_width_renders_a_number() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("terminal-width", &data, &config).unwrap();
+    let output = registry
+        .render("terminal-width", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // Should contain "cols" since raw_value is false
     assert!(output.text.contains("cols"));
@@ -709,36 +1259,52 @@ fn terminal_width_renders_a_number() {
 
 #[test]
 fn terminal_width_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("terminal-width", &data, &config).unwrap();
+    let output = registry
+        .render("terminal-width", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // Should be just a number
     assert!(output.text.parse::<u16>().is_ok());
 }
 
+#[test]
+fn terminal_width_comes_from_render_context() {
+    let registry = WidgetRegistry::builtin();
+    let ctx = RenderContext::new().with_terminal_width(132);
+    let output = registry
+        .render("terminal-width", &mock_session(), &ctx, &default_config())
+        .unwrap();
+    assert_eq!(output.text, "132 cols");
+}
+
 // ─── OutputStyleWidget ────────────────────────────────────────
 
 #[test]
 fn output_style_invisible_when_default() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session(); // output_style: "default"
     let config = default_config();
-    let output = registry.render("output-style", &data, &config).unwrap();
+    let output = registry
+        .render("output-style", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(!output.visible);
 }
 
 #[test]
 fn output_style_visible_when_non_default() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.output_style = Some(OutputStyle {
         name: Some("streaming".into()),
     });
     let config = default_config();
-    let output = registry.render("output-style", &data, &config).unwrap();
+    let output = registry
+        .render("output-style", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "streaming");
 }
@@ -747,10 +1313,12 @@ fn output_style_visible_when_non_default() {
 
 #[test]
 fn api_duration_renders_percentage() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("api-duration", &data, &config).unwrap();
+    let output = registry
+        .render("api-duration", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     // 156000/345000 * 100 = ~45%
     assert_eq!(output.text, "API: 45%");
@@ -758,11 +1326,13 @@ fn api_duration_renders_percentage() {
 
 #[test]
 fn api_duration_raw_value() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.raw_value = true;
-    let output = registry.render("api-duration", &data, &config).unwrap();
+    let output = registry
+        .render("api-duration", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "45%");
 }
@@ -771,7 +1341,7 @@ fn api_duration_raw_value() {
 
 #[test]
 fn all_widgets_with_empty_session_no_panic() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = empty_session();
     let config = default_config();
 
@@ -784,6 +1354,8 @@ fn all_widgets_with_empty_session_no_panic() {
         "tokens-cached",
         "tokens-total",
         "session-cost",
+        "turn-cost",
+        "cost-compare",
         "session-duration",
         "block-timer",
         "git-branch",
@@ -800,13 +1372,19 @@ fn all_widgets_with_empty_session_no_panic() {
         "api-duration",
         "custom-command",
         "custom-text",
+        "template",
         "separator",
         "flex-separator",
+        "zone",
         "terminal-width",
+        "last-tool",
+        "tool-count",
+        "last-prompt",
+        "idle-time",
     ];
 
     for name in &widget_names {
-        let result = registry.render(name, &data, &config);
+        let result = registry.render(name, &data, &RenderContext::new(), &config);
         assert!(result.is_some(), "Widget '{}' should be registered", name);
     }
 }
@@ -815,10 +1393,12 @@ fn all_widgets_with_empty_session_no_panic() {
 
 #[test]
 fn flex_separator_renders_fill_char() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("flex-separator", &data, &config).unwrap();
+    let output = registry
+        .render("flex-separator", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, " "); // default fill char is space
     assert_eq!(output.display_width, 0); // signals layout engine to expand
@@ -826,11 +1406,13 @@ fn flex_separator_renders_fill_char() {
 
 #[test]
 fn flex_separator_custom_char() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let mut config = default_config();
     config.metadata.insert("char".into(), "-".into());
-    let output = registry.render("flex-separator", &data, &config).unwrap();
+    let output = registry
+        .render("flex-separator", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert!(output.visible);
     assert_eq!(output.text, "-");
 }
@@ -839,18 +1421,18 @@ fn flex_separator_custom_char() {
 
 #[test]
 fn context_percentage_color_hint_green_below_50() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session(); // used_percentage: 42.5
     let config = default_config();
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert_eq!(output.color_hint, Some("green".into()));
 }
 
 #[test]
 fn context_percentage_color_hint_yellow_at_50_to_80() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.context_window = Some(ContextWindow {
         used_percentage: Some(65.0),
@@ -859,14 +1441,14 @@ fn context_percentage_color_hint_yellow_at_50_to_80() {
     });
     let config = default_config();
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert_eq!(output.color_hint, Some("yellow".into()));
 }
 
 #[test]
 fn context_percentage_color_hint_red_above_80() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let mut data = mock_session();
     data.context_window = Some(ContextWindow {
         used_percentage: Some(85.0),
@@ -875,25 +1457,543 @@ fn context_percentage_color_hint_red_above_80() {
     });
     let config = default_config();
     let output = registry
-        .render("context-percentage", &data, &config)
+        .render("context-percentage", &data, &RenderContext::new(), &config)
         .unwrap();
     assert_eq!(output.color_hint, Some("red".into()));
 }
 
 #[test]
 fn model_widget_has_no_color_hint() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let output = registry.render("model", &data, &config).unwrap();
+    let output = registry
+        .render("model", &data, &RenderContext::new(), &config)
+        .unwrap();
     assert_eq!(output.color_hint, None);
 }
 
 #[test]
 fn unknown_widget_returns_none() {
-    let registry = WidgetRegistry::new();
+    let registry = WidgetRegistry::builtin();
     let data = mock_session();
     let config = default_config();
-    let result = registry.render("nonexistent-widget", &data, &config);
+    let result = registry.render("nonexistent-widget", &data, &RenderContext::new(), &config);
     assert!(result.is_none());
 }
+
+// ─── Transcript widgets ────────────────────────────────────────
+
+fn transcript_session(name: &str, lines: &[&str]) -> SessionData {
+    let path = std::env::temp_dir().join(format!(
+        "claude-status-widget-transcript-{}-{name}.jsonl",
+        std::process::id()
+    ));
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    std::fs::write(&path, contents).unwrap();
+    SessionData {
+        session_id: Some(format!("widget-test-{name}")),
+        transcript_path: Some(path.to_string_lossy().into_owned()),
+        ..Default::default()
+    }
+}
+
+const PROMPT_LINE: &str = r#"{"type":"user","message":{"role":"user","content":"refactor the layout engine so widgets drop by priority"}}"#;
+const READ_LINE: &str = r#"{"type":"assistant","timestamp":"2020-01-01T00:00:00Z","message":{"role":"assistant","content":[{"type":"tool_use","name":"Read","input":{}}]}}"#;
+const EDIT_LINE: &str = r#"{"type":"assistant","timestamp":"2020-01-01T00:00:00Z","message":{"role":"assistant","content":[{"type":"tool_use","name":"Edit","input":{}},{"type":"tool_use","name":"Bash","input":{}}]}}"#;
+
+#[test]
+fn transcript_widgets_report_tools_and_prompt() {
+    let registry = WidgetRegistry::builtin();
+    let data = transcript_session("tools", &[PROMPT_LINE, READ_LINE, EDIT_LINE]);
+    let config = default_config();
+
+    let tool = registry
+        .render("last-tool", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(tool.text, "Tool: Bash");
+
+    let count = registry
+        .render("tool-count", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(count.text, "Tools: 3");
+
+    let prompt = registry
+        .render("last-prompt", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(
+        prompt.text,
+        "\u{201C}refactor the layout engine so\u{2026}\u{201D}"
+    );
+}
+
+#[test]
+fn transcript_widgets_share_one_read_per_render() {
+    let registry = WidgetRegistry::builtin();
+    let data = transcript_session("shared", &[PROMPT_LINE, READ_LINE]);
+    let config = default_config();
+    let ctx = RenderContext::new();
+
+    let tool = registry.render("last-tool", &data, &ctx, &config).unwrap();
+    assert_eq!(tool.text, "Tool: Read");
+
+    // Later widgets in the same render don't go back to the file
+    std::fs::remove_file(data.transcript_path.as_ref().unwrap()).unwrap();
+    let count = registry.render("tool-count", &data, &ctx, &config).unwrap();
+    assert_eq!(count.text, "Tools: 1");
+    let count = registry
+        .render("tool-count", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(!count.visible);
+}
+
+#[test]
+fn last_prompt_respects_max_length() {
+    let registry = WidgetRegistry::builtin();
+    let data = transcript_session("prompt", &[PROMPT_LINE]);
+    let mut config = default_config();
+    config.raw_value = true;
+    config.metadata.insert("max_length".into(), "8".into());
+    let output = registry
+        .render("last-prompt", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "refacto\u{2026}");
+}
+
+#[test]
+fn idle_time_counts_from_last_assistant_message() {
+    let registry = WidgetRegistry::builtin();
+    let data = transcript_session("idle", &[PROMPT_LINE, READ_LINE]);
+    let config = default_config();
+    let output = registry
+        .render("idle-time", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(output.visible);
+    assert!(output.text.starts_with("Idle: "));
+    assert!(output.text.ends_with('m'), "got: {}", output.text);
+}
+
+#[test]
+fn transcript_widgets_invisible_without_transcript() {
+    let registry = WidgetRegistry::builtin();
+    let data = empty_session();
+    let config = default_config();
+    for name in ["last-tool", "tool-count", "last-prompt", "idle-time"] {
+        let output = registry
+            .render(name, &data, &RenderContext::new(), &config)
+            .unwrap();
+        assert!(!output.visible, "{} should be hidden", name);
+    }
+}
+
+// ─── Git widgets ───────────────────────────────────────────────
+
+#[test]
+fn git_widgets_share_one_snapshot_of_a_real_repo() {
+    let dir =
+        std::env::temp_dir().join(format!("claude-status-git-widgets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let init = std::process::Command::new("git")
+        .args(["init", "-q", "-b", "trunk"])
+        .current_dir(&dir)
+        .status();
+    if !init.map(|s| s.success()).unwrap_or(false) {
+        return; // git not available
+    }
+    std::fs::write(dir.join("notes.txt"), "hello").unwrap();
+
+    let registry = WidgetRegistry::builtin();
+    let data = SessionData {
+        cwd: Some(dir.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let config = default_config();
+
+    let branch = registry
+        .render("git-branch", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(branch.text, "trunk");
+    let status = registry
+        .render("git-status", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(status.text, "?1");
+    let worktree = registry
+        .render("git-worktree", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert!(!worktree.visible);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn git_status_shows_ahead_and_stash_indicators() {
+    let dir = std::env::temp_dir().join(format!("claude-status-git-ahead-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    if !git(&["init", "-q", "-b", "trunk"]) {
+        return; // git not available
+    }
+    std::fs::write(dir.join("a.txt"), "one").unwrap();
+    assert!(git(&["add", "a.txt"]));
+    assert!(git(&["commit", "-q", "-m", "one"]));
+    assert!(git(&[
+        "checkout", "-q", "-b", "feature", "--track", "trunk"
+    ]));
+    std::fs::write(dir.join("a.txt"), "two").unwrap();
+    assert!(git(&["commit", "-q", "-am", "two"]));
+    std::fs::write(dir.join("a.txt"), "three").unwrap();
+    assert!(git(&["stash", "-q"]));
+
+    let registry = WidgetRegistry::builtin();
+    let data = SessionData {
+        cwd: Some(dir.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let mut config = default_config();
+    config.metadata.insert("upstream".into(), "true".into());
+    config.metadata.insert("ahead_behind".into(), "true".into());
+    config.metadata.insert("stash".into(), "true".into());
+
+    let output = registry
+        .render("git-status", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(output.text, "trunk ↑1 ≡1");
+    assert_eq!(output.color_hint, Some("yellow".into()));
+
+    let plain = registry
+        .render(
+            "git-status",
+            &data,
+            &RenderContext::new(),
+            &default_config(),
+        )
+        .unwrap();
+    assert_eq!(plain.text, "");
+    assert_eq!(plain.color_hint, None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn git_status_shows_conflicts_unless_turned_off() {
+    let dir =
+        std::env::temp_dir().join(format!("claude-status-git-conflict-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+            .args(args)
+            .current_dir(&dir)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    };
+    if !git(&["init", "-q", "-b", "trunk"]) {
+        return; // git not available
+    }
+    std::fs::write(dir.join("a.txt"), "one").unwrap();
+    assert!(git(&["add", "a.txt"]));
+    assert!(git(&["commit", "-q", "-m", "one"]));
+    assert!(git(&["checkout", "-q", "-b", "other"]));
+    std::fs::write(dir.join("a.txt"), "two").unwrap();
+    assert!(git(&["commit", "-q", "-am", "two"]));
+    assert!(git(&["checkout", "-q", "trunk"]));
+    std::fs::write(dir.join("a.txt"), "three").unwrap();
+    assert!(git(&["commit", "-q", "-am", "three"]));
+    assert!(!git(&["merge", "-q", "other"]));
+
+    let registry = WidgetRegistry::builtin();
+    let data = SessionData {
+        cwd: Some(dir.to_string_lossy().into_owned()),
+        ..Default::default()
+    };
+    let output = registry
+        .render(
+            "git-status",
+            &data,
+            &RenderContext::new(),
+            &default_config(),
+        )
+        .unwrap();
+    assert_eq!(output.text, "!1");
+    assert_eq!(output.color_hint, Some("red".into()));
+
+    let mut config = default_config();
+    config.metadata.insert("conflicts".into(), "false".into());
+    let hidden = registry
+        .render("git-status", &data, &RenderContext::new(), &config)
+        .unwrap();
+    assert_eq!(hidden.text, "");
+    assert_eq!(hidden.color_hint, None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ─── Pro widgets ───────────────────────────────────────────────
+
+fn pro_license() -> claude_status::license::LicenseInfo {
+    use claude_status::license::{LicenseInfo, LicenseStatus, LicenseTier};
+
+    LicenseInfo {
+        tier: LicenseTier::Pro,
+        status: LicenseStatus::Valid,
+        key: "test".into(),
+        expires: None,
+        features: Vec::new(),
+        last_validated: None,
+        machine_id: "test".into(),
+    }
+}
+
+/// A history with one event per `(seconds before now, cost)`.
+fn tracker_with_costs(now: i64, costs: &[(i64, f64)]) -> claude_status::CostTracker {
+    use claude_status::storage::{CostEvent, SessionRecord};
+
+    let tracker = claude_status::CostTracker::open_in_memory().unwrap();
+    let session = SessionRecord::from_session_data(&mock_session(), now).unwrap();
+    tracker.upsert_session(&session).unwrap();
+    for (ago, cost) in costs {
+        tracker
+            .insert_event(&CostEvent {
+                id: None,
+                session_id: session.id.clone(),
+                timestamp: now - ago,
+                event_type: "cost".into(),
+                cost: *cost,
+                metadata: None,
+            })
+            .unwrap();
+    }
+    tracker
+}
+
+#[test]
+fn pro_widgets_hidden_without_license() {
+    let registry = WidgetRegistry::builtin();
+    let ctx = RenderContext::new().with_license(None);
+    for name in ["burn-rate", "cost-warning", "model-suggest"] {
+        let output = registry
+            .render(name, &mock_session(), &ctx, &default_config())
+            .unwrap();
+        assert!(!output.visible, "{name} shown without a license");
+    }
+}
+
+#[test]
+fn cost_warning_reads_week_from_render_context() {
+    // Wednesday noon; the week started on Monday
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-11T12:00:00Z")
+        .unwrap()
+        .to_utc();
+    let ts = now.timestamp();
+    let tracker = tracker_with_costs(ts, &[(3600, 50.0), (86400, 30.0), (4 * 86400, 500.0)]);
+    let ctx = RenderContext::new()
+        .with_now(now)
+        .with_license(Some(pro_license()))
+        .with_tracker(tracker);
+
+    let registry = WidgetRegistry::builtin();
+    let mut config = default_config();
+    config.metadata.insert("weekly_limit".into(), "100".into());
+    let output = registry
+        .render("cost-warning", &mock_session(), &ctx, &config)
+        .unwrap();
+    assert!(output.visible);
+    assert_eq!(
+        output.text,
+        "\u{26A0}\u{FE0F} 80% of weekly limit ($80/$100)"
+    );
+    assert_eq!(output.value, Some(80.0));
+}
+
+#[test]
+fn burn_rate_uses_render_context_clock() {
+    let now = chrono::Utc::now() - chrono::Duration::days(30);
+    let ts = now.timestamp();
+    // $3 in the last hour; the older event is outside the window
+    let tracker = tracker_with_costs(ts, &[(600, 1.0), (1800, 2.0), (7200, 40.0)]);
+    let ctx = RenderContext::new()
+        .with_now(now)
+        .with_license(Some(pro_license()))
+        .with_tracker(tracker);
+
+    let registry = WidgetRegistry::builtin();
+    let mut config = default_config();
+    config.raw_value = true;
+    let output = registry
+        .render("burn-rate", &mock_session(), &ctx, &config)
+        .unwrap();
+    assert_eq!(output.text, "3.00");
+}
+
+#[test]
+fn cost_warning_follows_calendar_week_start() {
+    // Wednesday noon; with weeks starting on Saturday, the $500 on
+    // Saturday counts
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-11T12:00:00Z")
+        .unwrap()
+        .to_utc();
+    let ts = now.timestamp();
+    let tracker = tracker_with_costs(ts, &[(3600, 50.0), (86400, 30.0), (4 * 86400, 500.0)]);
+    let calendar = claude_status::calendar::Calendar {
+        week_start: chrono::Weekday::Sat,
+        ..Default::default()
+    };
+    let ctx = RenderContext::new()
+        .with_now(now)
+        .with_license(Some(pro_license()))
+        .with_tracker(tracker)
+        .with_calendar(calendar);
+
+    let registry = WidgetRegistry::builtin();
+    let mut config = default_config();
+    config.metadata.insert("weekly_limit".into(), "100".into());
+    let output = registry
+        .render("cost-warning", &mock_session(), &ctx, &config)
+        .unwrap();
+    assert_eq!(output.text, "\u{1F534} 580% of weekly limit ($580/$100)");
+}
+
+#[test]
+fn burn_rate_counts_down_what_is_left_of_the_week() {
+    // Wednesday noon: $3 in the last hour, $43 so far this week
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-11T12:00:00Z")
+        .unwrap()
+        .to_utc();
+    let tracker = tracker_with_costs(now.timestamp(), &[(600, 1.0), (1800, 2.0), (86400, 40.0)]);
+    let ctx = RenderContext::new()
+        .with_now(now)
+        .with_license(Some(pro_license()))
+        .with_tracker(tracker);
+
+    let registry = WidgetRegistry::builtin();
+    let render = |weekly_limit: &str| {
+        let mut config = default_config();
+        config
+            .metadata
+            .insert("weekly_limit".into(), weekly_limit.into());
+        registry
+            .render("burn-rate", &mock_session(), &ctx, &config)
+            .unwrap()
+            .text
+    };
+    assert_eq!(render("49"), "Burn: $3.00/hr -> limit in 2h 0m");
+    assert_eq!(render("40"), "Burn: $3.00/hr -> limit reached");
+    // The week resets on Monday, long before $957 more is spent
+    assert_eq!(render("1000"), "Burn: $3.00/hr");
+}
+
+// ─── ProjectBudgetWidget ──────────────────────────────────────
+
+/// Wednesday noon, with $5 spent on the project today and $30 yesterday,
+/// and the mock session's project under a `[budgets]` glob.
+fn budget_context(daily: f64, weekly: f64) -> RenderContext {
+    use claude_status::budget::{Budget, Budgets};
+
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-11T12:00:00Z")
+        .unwrap()
+        .to_utc();
+    let tracker = tracker_with_costs(now.timestamp(), &[(3600, 5.0), (86400, 30.0)]);
+    let budget = Budget {
+        daily: Some(daily),
+        weekly: Some(weekly),
+        monthly: None,
+    };
+    RenderContext::new()
+        .with_now(now)
+        .with_license(Some(pro_license()))
+        .with_tracker(tracker)
+        .with_budgets(Budgets::new(&HashMap::from([(
+            "/Users/test/*".to_string(),
+            budget,
+        )])))
+}
+
+#[test]
+fn project_budget_shows_tightest_limit() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let config = default_config();
+
+    // Half the daily limit, 35% of the weekly one
+    let output = registry
+        .render(
+            "project-budget",
+            &data,
+            &budget_context(10.0, 100.0),
+            &config,
+        )
+        .unwrap();
+    assert_eq!(output.text, "project: $5.00 left today");
+    assert_eq!(output.color_hint, None);
+    assert_eq!(output.value, Some(50.0));
+
+    let output = registry
+        .render(
+            "project-budget",
+            &data,
+            &budget_context(100.0, 38.0),
+            &config,
+        )
+        .unwrap();
+    assert_eq!(output.text, "project: $3.00 left this week");
+    assert_eq!(output.color_hint.as_deref(), Some("red"));
+}
+
+#[test]
+fn project_budget_colors_by_threshold() {
+    let registry = WidgetRegistry::builtin();
+    let data = mock_session();
+    let mut config = default_config();
+
+    let output = registry
+        .render(
+            "project-budget",
+            &data,
+            &budget_context(6.5, 100.0),
+            &config,
+        )
+        .unwrap();
+    assert_eq!(output.color_hint.as_deref(), Some("yellow"));
+
+    let ctx = budget_context(4.0, 100.0);
+    let output = registry
+        .render("project-budget", &data, &ctx, &config)
+        .unwrap();
+    assert_eq!(output.text, "project: $1.00 over today");
+    assert_eq!(output.color_hint.as_deref(), Some("red"));
+
+    config.compact = true;
+    let output = registry
+        .render("project-budget", &data, &ctx, &config)
+        .unwrap();
+    assert_eq!(output.text, "$1.00 over");
+}
+
+#[test]
+fn project_budget_hidden_without_matching_budget() {
+    let registry = WidgetRegistry::builtin();
+    let mut data = mock_session();
+    data.workspace.as_mut().unwrap().project_dir = Some("/srv/other".into());
+    let output = registry
+        .render(
+            "project-budget",
+            &data,
+            &budget_context(10.0, 100.0),
+            &default_config(),
+        )
+        .unwrap();
+    assert!(!output.visible);
+}
@@ -0,0 +1,158 @@
+use claude_status::config::Config;
+use claude_status::layout::{LayoutEngine, display_width};
+use claude_status::render::Renderer;
+use claude_status::widgets::{RenderContext, SessionData, WidgetConfig, WidgetRegistry};
+
+// ─── Corpus ──────────────────────────────────────────────────
+
+/// Text and the columns a terminal gives it.
+const CORPUS: &[(&str, usize)] = &[
+    ("main", 4),
+    ("", 0),
+    // Box drawing and block elements used by the bars: one column, three bytes
+    ("▓▓▓▓░░░░░░", 10),
+    ("│", 1),
+    // Accented Latin, precomposed and combining
+    ("café", 4),
+    ("cafe\u{301}", 4),
+    // CJK branch names are two columns per character
+    ("功能/登录", 9),
+    ("feature/日本語", 14),
+    // Emoji, with and without presentation selectors
+    ("🔴", 2),
+    ("⚠", 1),
+    ("⚠\u{fe0f}", 2),
+    ("✔\u{fe0e}", 1),
+    ("❤\u{fe0f}", 2),
+    // ZWJ sequences, skin tones, flags and keycaps render as one glyph
+    ("👨\u{200d}👩\u{200d}👧", 2),
+    ("🏳\u{fe0f}\u{200d}🌈", 2),
+    ("👍🏽", 2),
+    ("🇯🇵", 2),
+    ("1\u{fe0f}\u{20e3}", 2),
+    // Escape sequences take no columns
+    ("\x1b[31mred\x1b[0m", 3),
+    ("\x1b[38;5;208m⚠\u{fe0f} 85%\x1b[0m", 6),
+    ("\x1b]8;;https://example.com/功能\x07link\x1b]8;;\x07", 4),
+    ("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\", 4),
+];
+
+#[test]
+fn corpus_widths() {
+    for (text, expected) in CORPUS {
+        assert_eq!(display_width(text), *expected, "width of {text:?}");
+    }
+}
+
+// ─── Widget outputs ──────────────────────────────────────────
+
+fn session(json: serde_json::Value) -> SessionData {
+    serde_json::from_value(json).unwrap()
+}
+
+fn widget_config(widget_type: &str, metadata: &[(&str, &str)]) -> WidgetConfig {
+    WidgetConfig {
+        widget_type: widget_type.into(),
+        metadata: metadata
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn registry_measures_rendered_text() {
+    let registry = WidgetRegistry::builtin();
+    let data = session(serde_json::json!({
+        "cwd": "/home/dev/プロジェクト",
+        "model": { "display_name": "Opus ⚡" },
+        "context_window": { "used_percentage": 42.0 }
+    }));
+    let cases = [
+        ("model", widget_config("model", &[])),
+        ("cwd", widget_config("cwd", &[])),
+        (
+            "context-percentage",
+            widget_config("context-percentage", &[("bar", "true")]),
+        ),
+        (
+            "custom-text",
+            widget_config("custom-text", &[("text", "⚠\u{fe0f} 功能")]),
+        ),
+        ("separator", widget_config("separator", &[("char", "│")])),
+    ];
+    for (widget_type, config) in cases {
+        let output = registry
+            .render(widget_type, &data, &RenderContext::new(), &config)
+            .unwrap();
+        assert!(output.visible, "{widget_type} hidden");
+        assert_eq!(
+            output.display_width,
+            display_width(&output.text),
+            "{widget_type}: {:?}",
+            output.text
+        );
+        assert!(output.display_width < output.text.len(), "{widget_type}");
+    }
+}
+
+// ─── Layout ──────────────────────────────────────────────────
+
+fn render_compact(source: &str, powerline: bool) -> String {
+    let mut config: Config = toml::from_str(source).unwrap();
+    // "compact" flex mode pins the available width to 60 columns
+    config.flex_mode = "compact".into();
+    config.powerline.enabled = powerline;
+    let data = session(serde_json::json!({
+        "cwd": "/home/dev/功能",
+        "model": { "display_name": "Opus ⚡" }
+    }));
+    let renderer = Renderer::detect("none");
+    let registry = WidgetRegistry::builtin();
+    let engine = LayoutEngine::new(&config, &renderer);
+    engine.render(&data, &registry).remove(0)
+}
+
+const FLEX_LINE: &str = r#"
+lines = [[
+  { type = "model" },
+  { type = "custom-text", metadata = { text = "🔴 👨‍👩‍👧" } },
+  { type = "flex-separator" },
+  { type = "cwd" },
+]]
+"#;
+
+#[test]
+fn flex_separator_fills_exactly_with_wide_text() {
+    let line = render_compact(FLEX_LINE, false);
+    assert_eq!(display_width(&line), 60, "{line:?}");
+}
+
+#[test]
+fn powerline_flex_fills_exactly_with_wide_text() {
+    let line = render_compact(FLEX_LINE, true);
+    assert_eq!(display_width(&line), 60, "{line:?}");
+}
+
+#[test]
+fn powerline_flex_at_exact_width_keeps_every_widget() {
+    // 27 + 2 padding per side, plus the closing and reverse separators
+    let line = render_compact(
+        &format!(
+            r#"
+lines = [[
+  {{ type = "custom-text", metadata = {{ text = "{left}" }} }},
+  {{ type = "flex-separator" }},
+  {{ type = "custom-text", metadata = {{ text = "{right}" }} }},
+]]
+"#,
+            left = "l".repeat(27),
+            right = "r".repeat(27),
+        ),
+        true,
+    );
+    assert!(line.contains(&"l".repeat(27)), "{line:?}");
+    assert!(line.contains(&"r".repeat(27)), "{line:?}");
+    assert_eq!(display_width(&line), 60, "{line:?}");
+}